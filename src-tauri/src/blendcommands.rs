@@ -0,0 +1,351 @@
+use crate::bookmarkcommands::GlobalBookmarks;
+use crate::constraints::enforce_constraints;
+use crate::filerandomisercommands::{
+    build_pick_diagnostics, choose_existing, commit_pick, crawl_files, finish_crawl_job,
+    prune_missing_files, remap_persisted_state, resolve_path_variables, sample_weights,
+    score_candidates, start_crawl_job, CrawlMonitor, PathPickCounts,
+};
+use crate::historycommands::path_key;
+use crate::models::{
+    AppStateData, BlendListing, BlendPick, BookmarkIndex, FileEntry, InvalidPresetFile,
+    PresetBlend, SavedPath,
+};
+use crate::persistence::write_atomic;
+use crate::presetcommands::load_presets;
+use crate::sessioncommands::PickSessions;
+use crate::setting_commands::get_app_settings;
+use rand::distr::weighted::WeightedIndex;
+use rand::prelude::*;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+use tauri::State;
+
+/// A preset's crawled files inside a loaded blend. File IDs are shared with
+/// `AppStateData.files` so pick counts, recency and history stay unified.
+pub struct BlendPool {
+    pub preset_id: String,
+    pub preset_name: String,
+    pub weight: f64,
+    pub files: Vec<FileEntry>,
+    pub path_weights: HashMap<String, f64>,
+}
+
+pub struct BlendSession {
+    pub blend: PresetBlend,
+    pub pools: Vec<BlendPool>,
+}
+
+pub struct LoadedBlend(pub Mutex<Option<BlendSession>>);
+
+fn blends_dir(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("blends");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// Blends on disk, plus any files in the blends folder that failed to load
+#[tauri::command]
+pub fn get_blends(app: tauri::AppHandle) -> Result<BlendListing, String> {
+    let mut listing = BlendListing::default();
+
+    for entry in std::fs::read_dir(blends_dir(&app)?).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                serde_json::from_str::<PresetBlend>(&content).map_err(|e| e.to_string())
+            });
+        match parsed {
+            Ok(blend) => listing.blends.push(blend),
+            Err(error) => listing.invalid.push(InvalidPresetFile { file_name, error }),
+        }
+    }
+
+    Ok(listing)
+}
+
+#[tauri::command]
+pub fn save_blend(app: tauri::AppHandle, blend: PresetBlend) -> Result<(), String> {
+    if blend.components.is_empty() {
+        return Err("A blend needs at least one preset".into());
+    }
+    if blend
+        .components
+        .iter()
+        .any(|c| !c.weight.is_finite() || c.weight < 0.0)
+    {
+        return Err("Blend weights must be zero or positive".into());
+    }
+    if blend.components.iter().map(|c| c.weight).sum::<f64>() <= 0.0 {
+        return Err("At least one blend weight must be positive".into());
+    }
+
+    // Keyed on id so renaming a blend never leaves a stale file behind
+    let safe_id = blend
+        .id
+        .replace(|c: char| !c.is_alphanumeric() && c != '-' && c != '_', "_");
+    let file_path = blends_dir(&app)?.join(format!("{}.json", safe_id));
+    let json_data = serde_json::to_string_pretty(&blend).map_err(|e| e.to_string())?;
+
+    write_atomic(&file_path, json_data.as_bytes())?;
+
+    Ok(())
+}
+
+/// Crawl every preset in the blend in the background and make the union the
+/// active file list. Each preset keeps its own filtered pool for
+/// `pick_blended_file`. Runs as the current crawl, so it emits
+/// "crawl-progress" and can be cancelled; returns None if it was.
+#[tauri::command]
+pub async fn load_blend(
+    app: tauri::AppHandle,
+    blend_id: String,
+) -> Result<Option<Vec<FileEntry>>, String> {
+    let blend = get_blends(app.clone())?
+        .blends
+        .into_iter()
+        .find(|b| b.id == blend_id)
+        .ok_or_else(|| format!("Blend not found: {}", blend_id))?;
+    let presets = load_presets(&app)?.presets;
+    let components = blend
+        .components
+        .iter()
+        .map(|component| {
+            presets
+                .iter()
+                .find(|p| p.id == component.preset_id)
+                .cloned()
+                .map(|preset| (preset, component.weight))
+                .ok_or_else(|| format!("Blend references missing preset {}", component.preset_id))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let settings = get_app_settings(app.clone())?;
+    let path_variables = resolve_path_variables(&app, &settings);
+    let global_bookmarks = app.state::<GlobalBookmarks>().0.lock().unwrap().clone();
+
+    let cancel = start_crawl_job(&app);
+    let monitor = CrawlMonitor::new(app.clone(), Arc::clone(&cancel));
+    let walk_variables = path_variables.clone();
+    let crawled = tauri::async_runtime::spawn_blocking(move || {
+        // The same file can appear in several presets; give it one ID per path
+        let mut path_ids: HashMap<String, u64> = HashMap::new();
+        let mut union: Vec<FileEntry> = Vec::new();
+        let mut paths: Vec<SavedPath> = Vec::new();
+        let mut pools = Vec::with_capacity(components.len());
+
+        for (preset, weight) in components {
+            let local_bookmarks = BookmarkIndex::from(preset.bookmarks.clone());
+            let mut files = crawl_files(
+                &walk_variables.expand_saved_paths(&preset.paths),
+                &preset.filter_rules,
+                &global_bookmarks,
+                &local_bookmarks,
+                &AtomicU64::new(1),
+                &monitor,
+            )?;
+
+            for file in files.iter_mut() {
+                let next_id = union.len() as u64 + 1;
+                let id = *path_ids.entry(path_key(&file.path)).or_insert(next_id);
+                file.id = id;
+                if id == next_id {
+                    union.push(file.clone());
+                } else if !file.excluded {
+                    // Only excluded from the union if every preset excludes it
+                    union[(id - 1) as usize].excluded = false;
+                }
+            }
+
+            for saved in &preset.paths {
+                let key = path_key(&saved.path);
+                if !paths.iter().any(|p| path_key(&p.path) == key) {
+                    paths.push(SavedPath {
+                        id: paths.len() as u64 + 1,
+                        ..saved.clone()
+                    });
+                }
+            }
+
+            pools.push(BlendPool {
+                preset_id: preset.id,
+                preset_name: preset.name,
+                weight,
+                files,
+                path_weights: preset.path_weights,
+            });
+        }
+        monitor.emit(monitor.progress());
+        Some((union, paths, pools))
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Lock order matches pick_blended_file: blend session before app state
+    let loaded_blend = app.state::<LoadedBlend>();
+    let mut session = loaded_blend.0.lock().unwrap();
+    let app_data = app.state::<Mutex<AppStateData>>();
+    let mut data = app_data.lock().unwrap();
+    let Some((union, paths, mut pools)) = crawled.filter(|_| finish_crawl_job(&app, &cancel))
+    else {
+        return Ok(None);
+    };
+
+    data.paths = paths;
+    data.files = union;
+    // Filters and path weights are applied per preset inside the blend
+    data.filter_rules.clear();
+    data.preset_path_weights.clear();
+    data.last_picked_id = None;
+    data.last_picked_index = None;
+    data.recency_list.clear();
+    data.path_variables = path_variables;
+    remap_persisted_state(&app, &mut data, &app.state::<PathPickCounts>());
+    // Pool files share the union's ids; carry over the first-seen stamps
+    for pool in pools.iter_mut() {
+        for file in pool.files.iter_mut() {
+            file.first_seen = data.files[(file.id - 1) as usize].first_seen;
+        }
+    }
+
+    *session = Some(BlendSession { blend, pools });
+
+    Ok(Some(data.files.clone()))
+}
+
+/// Sample a preset by blend weight, then a file within it using the normal
+/// weighting. Presets with no candidates are skipped and the rest renormalised.
+#[tauri::command]
+pub fn pick_blended_file(
+    app: tauri::AppHandle,
+    app_data: State<'_, Mutex<AppStateData>>,
+    loaded_blend: State<'_, LoadedBlend>,
+    sessions: State<'_, PickSessions>,
+) -> Result<Option<BlendPick>, String> {
+    let settings = get_app_settings(app.clone())?;
+    let mut rng = rand::rng();
+
+    let mut session_guard = loaded_blend.0.lock().unwrap();
+    let session = session_guard.as_ref().ok_or("No blend loaded")?;
+
+    let mut data = app_data.lock().unwrap();
+
+    let pool_candidates: Vec<Vec<(usize, &FileEntry)>> = session
+        .pools
+        .iter()
+        .map(|pool| {
+            pool.files
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| !f.excluded)
+                .collect()
+        })
+        .collect();
+    let mut pool_weights: Vec<f64> = session
+        .pools
+        .iter()
+        .zip(pool_candidates.iter())
+        .map(|(pool, candidates)| {
+            if candidates.is_empty() {
+                0.0
+            } else {
+                pool.weight
+            }
+        })
+        .collect();
+    if pool_weights.iter().sum::<f64>() <= 0.0 {
+        return Ok(None);
+    }
+
+    let session_id = sessions
+        .0
+        .lock()
+        .unwrap()
+        .continuing(&settings, Some(&session.blend.id), chrono::Utc::now())
+        .map(|s| s.id.clone());
+    let mut missing_ids: Vec<u64> = vec![];
+    // A preset that yields no pick, because its chosen files all turned out
+    // to be missing or nothing in it can be picked, is dropped and another
+    // one sampled
+    let picked = loop {
+        let Ok(dist) = WeightedIndex::new(&pool_weights) else {
+            break None;
+        };
+        let pool_index = dist.sample(&mut rng);
+        let pool = &session.pools[pool_index];
+        let candidates = &pool_candidates[pool_index];
+        let preset_probability = pool_weights[pool_index] / pool_weights.iter().sum::<f64>();
+
+        let mut scores = score_candidates(candidates, &data, &settings, &pool.path_weights);
+        let check = enforce_constraints(
+            candidates,
+            &mut scores.weights,
+            &data,
+            &settings.file_randomiser.pick_constraints,
+            session_id.as_deref(),
+        );
+        let (chosen, missing) = choose_existing(candidates, |is_missing| {
+            sample_weights(&mut scores.weights, is_missing, &mut rng)
+        });
+        missing_ids.extend(missing.iter().map(|i| candidates[*i].1.id));
+        let Some((chosen, _)) = chosen else {
+            pool_weights[pool_index] = 0.0;
+            continue;
+        };
+        let file = candidates[chosen].1.clone();
+
+        let mut diagnostics = build_pick_diagnostics(candidates, &scores, chosen, &data, &settings);
+        diagnostics.constraints_applied = check.applied;
+        diagnostics.constraint_violations = check.violations(&file);
+        diagnostics.blend_id = Some(session.blend.id.clone());
+        diagnostics.blend_preset_id = Some(pool.preset_id.clone());
+        diagnostics.blend_preset_probability = Some(preset_probability);
+
+        let pick = BlendPick {
+            file,
+            preset_id: pool.preset_id.clone(),
+            preset_name: pool.preset_name.clone(),
+            preset_probability,
+        };
+        break Some((pick, scores.recency_window, diagnostics));
+    };
+
+    if !missing_ids.is_empty() {
+        if let Some(session) = session_guard.as_mut() {
+            for pool in session.pools.iter_mut() {
+                pool.files.retain(|f| !missing_ids.contains(&f.id));
+            }
+        }
+        prune_missing_files(&app, &mut data, &missing_ids);
+    }
+    drop(session_guard);
+
+    let Some((pick, recency_window, mut diagnostics)) = picked else {
+        return Ok(None);
+    };
+    diagnostics.skipped_missing = missing_ids.len() as u32;
+    commit_pick(
+        app,
+        data,
+        &settings,
+        &pick.file,
+        recency_window,
+        diagnostics,
+    );
+
+    Ok(Some(pick))
+}
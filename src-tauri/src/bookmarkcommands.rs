@@ -0,0 +1,293 @@
+use crate::migrations::{SchemaKind, BOOKMARKS_SCHEMA_VERSION};
+use crate::models::{Bookmark, BookmarkChange, BookmarkIndex, PersistedBookmarks};
+use crate::persistence::{load_with_recovery, quarantine, save_with_backups, write_atomic};
+use crate::setting_commands::get_app_settings;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State, Wry};
+use tauri_plugin_store::StoreExt;
+
+pub struct GlobalBookmarks(pub Mutex<BookmarkIndex>);
+
+/// Cleared when bookmarks.json could not be read or moved aside, so the
+/// empty in-memory index is never saved over it
+pub struct BookmarksWritable(pub AtomicBool);
+
+/// Number of changes in the journal since bookmarks.json was last written
+pub struct BookmarkJournal(pub Mutex<usize>);
+
+/// The journal is folded into a fresh bookmarks.json past this many changes
+const COMPACT_AFTER: usize = 1000;
+
+pub(crate) fn bookmarks_file_path(app: &AppHandle<Wry>) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(|d| d.join("bookmarks.json"))
+}
+
+/// Single-bookmark edits are appended here, one compact JSON change per line,
+/// so an edit costs O(1) however many bookmarks there are
+pub(crate) fn bookmark_journal_path(app: &AppHandle<Wry>) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(|d| d.join("bookmarks.journal.jsonl"))
+}
+
+fn check_writable(app: &AppHandle<Wry>) -> Result<(), String> {
+    if app.state::<BookmarksWritable>().0.load(Ordering::SeqCst) {
+        Ok(())
+    } else {
+        Err("Global bookmarks failed to load; not saving over bookmarks.json".into())
+    }
+}
+
+/// Write the whole index to bookmarks.json and empty the journal it supersedes
+fn save_global_bookmarks(app: &AppHandle<Wry>, index: &BookmarkIndex) -> Result<(), String> {
+    check_writable(app)?;
+    let path = bookmarks_file_path(app).ok_or("App data directory unavailable")?;
+    save_with_backups(&path, bookmarks_json(index)?.as_bytes())?;
+    if let Some(journal) = bookmark_journal_path(app).filter(|p| p.exists()) {
+        write_atomic(&journal, b"")?;
+    }
+    *app.state::<BookmarkJournal>().0.lock().unwrap() = 0;
+    Ok(())
+}
+
+/// Append `changes` to the journal, compacting it into bookmarks.json once it
+/// grows past `COMPACT_AFTER`. `index` must already include the changes.
+fn record_changes(
+    app: &AppHandle<Wry>,
+    index: &BookmarkIndex,
+    changes: &[BookmarkChange],
+) -> Result<(), String> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+    check_writable(app)?;
+    let path = bookmark_journal_path(app).ok_or("App data directory unavailable")?;
+    let mut lines = String::new();
+    for change in changes {
+        lines.push_str(&serde_json::to_string(change).map_err(|e| e.to_string())?);
+        lines.push('\n');
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| e.to_string())?;
+    file.write_all(lines.as_bytes())
+        .map_err(|e| e.to_string())?;
+    file.sync_data().map_err(|e| e.to_string())?;
+
+    let journal = app.state::<BookmarkJournal>();
+    let pending = {
+        let mut pending = journal.0.lock().unwrap();
+        *pending += changes.len();
+        *pending
+    };
+    if pending >= COMPACT_AFTER {
+        save_global_bookmarks(app, index)?;
+    }
+    Ok(())
+}
+
+/// Apply the journal's changes to `index`. Lines that fail to parse, such as
+/// one torn by a crash mid-append, are skipped. Returns (applied, skipped).
+fn replay_journal(path: &Path, index: &mut BookmarkIndex) -> (usize, usize) {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return (0, 0);
+    };
+    let (mut applied, mut skipped) = (0, 0);
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        match serde_json::from_str::<BookmarkChange>(line) {
+            Ok(BookmarkChange::Upsert { bookmark }) => index.upsert(bookmark),
+            Ok(BookmarkChange::Remove { hash }) => {
+                index.remove(&hash);
+            }
+            Err(_) => {
+                skipped += 1;
+                continue;
+            }
+        }
+        applied += 1;
+    }
+    (applied, skipped)
+}
+
+/// On-disk contents of bookmarks.json
+pub(crate) fn bookmarks_json(index: &BookmarkIndex) -> Result<String, String> {
+    let persisted = PersistedBookmarks {
+        schema_version: BOOKMARKS_SCHEMA_VERSION,
+        bookmarks: index.to_vec(),
+    };
+    // Compact JSON: this file can hold tens of thousands of entries
+    serde_json::to_string(&persisted).map_err(|e| e.to_string())
+}
+
+/// Load bookmarks.json and replay the journal into managed state, recovering
+/// from backups like the stats file. An unreadable file is quarantined along
+/// with its journal; if even that fails, saving is disabled for the session.
+/// On first run after upgrading, bookmarks are moved out of
+/// `file_randomiser.global_bookmarks` in store.json.
+pub fn load_global_bookmarks(app: &AppHandle<Wry>) {
+    let (Some(path), Some(journal)) = (bookmarks_file_path(app), bookmark_journal_path(app)) else {
+        return;
+    };
+
+    let (mut index, legacy) =
+        match load_with_recovery::<PersistedBookmarks>(&path, SchemaKind::Bookmarks) {
+            Ok(Some(persisted)) => (BookmarkIndex::from(persisted.bookmarks), false),
+            Ok(None) => {
+                let Ok(settings) = get_app_settings(app.clone()) else {
+                    return;
+                };
+                let index = BookmarkIndex::from(settings.file_randomiser.global_bookmarks);
+                (index, true)
+            }
+            Err(e) => {
+                eprintln!("Failed to load global bookmarks: {}", e);
+                // The journal only makes sense on top of the lost file
+                let moved = quarantine(&path).and_then(|moved| {
+                    if journal.exists() {
+                        quarantine(&journal)?;
+                    }
+                    Ok(moved)
+                });
+                match moved {
+                    Ok(moved) => eprintln!("Moved unreadable bookmarks to {:?}", moved),
+                    Err(e) => {
+                        eprintln!("Failed to move unreadable bookmarks aside: {}", e);
+                        app.state::<BookmarksWritable>()
+                            .0
+                            .store(false, Ordering::SeqCst);
+                    }
+                }
+                return;
+            }
+        };
+
+    let (applied, skipped) = replay_journal(&journal, &mut index);
+    if skipped > 0 {
+        eprintln!(
+            "Skipped {} unreadable bookmark changes in {:?}",
+            skipped, journal
+        );
+    }
+    if legacy || applied + skipped > 0 {
+        match save_global_bookmarks(app, &index) {
+            Ok(()) if legacy => strip_legacy_global_bookmarks(app),
+            Ok(()) => {}
+            Err(e) if legacy => {
+                eprintln!("Failed to migrate global bookmarks: {}", e);
+                return;
+            }
+            Err(e) => eprintln!("Failed to compact bookmark journal: {}", e),
+        }
+    }
+
+    *app.state::<GlobalBookmarks>().0.lock().unwrap() = index;
+}
+
+/// Drop `globalBookmarks` from the settings blob in store.json once the
+/// bookmarks have been safely written to their own file.
+fn strip_legacy_global_bookmarks(app: &AppHandle<Wry>) {
+    let Ok(store) = app.store("store.json") else {
+        return;
+    };
+    let Some(mut value) = store.get("settings") else {
+        return;
+    };
+    if let Some(randomiser) = value
+        .get_mut("fileRandomiser")
+        .and_then(|v| v.as_object_mut())
+    {
+        if randomiser.remove("globalBookmarks").is_some() {
+            store.set("settings", value);
+            let _ = store.save();
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_global_bookmarks(store: State<'_, GlobalBookmarks>) -> Vec<Bookmark> {
+    store.0.lock().unwrap().to_vec()
+}
+
+/// Replace the whole global bookmark list
+#[tauri::command]
+pub fn set_global_bookmarks(
+    app: AppHandle<Wry>,
+    store: State<'_, GlobalBookmarks>,
+    bookmarks: Vec<Bookmark>,
+) -> Result<Vec<Bookmark>, String> {
+    let mut index = store.0.lock().unwrap();
+    *index = BookmarkIndex::from(bookmarks);
+    save_global_bookmarks(&app, &index)?;
+    Ok(index.to_vec())
+}
+
+/// Add new global bookmarks or replace existing ones with the same hash.
+/// Returns the stored bookmarks so callers can update their copy in place.
+#[tauri::command]
+pub fn upsert_global_bookmarks(
+    app: AppHandle<Wry>,
+    store: State<'_, GlobalBookmarks>,
+    bookmarks: Vec<Bookmark>,
+) -> Result<Vec<Bookmark>, String> {
+    let mut index = store.0.lock().unwrap();
+    let changes: Vec<BookmarkChange> = bookmarks
+        .iter()
+        .map(|bm| BookmarkChange::Upsert {
+            bookmark: bm.clone(),
+        })
+        .collect();
+    for bm in bookmarks.iter().cloned() {
+        index.upsert(bm);
+    }
+    record_changes(&app, &index, &changes)?;
+    Ok(bookmarks)
+}
+
+/// Remove global bookmarks by hash. Returns how many were removed.
+#[tauri::command]
+pub fn remove_global_bookmarks(
+    app: AppHandle<Wry>,
+    store: State<'_, GlobalBookmarks>,
+    hashes: Vec<String>,
+) -> Result<usize, String> {
+    let mut index = store.0.lock().unwrap();
+    let changes: Vec<BookmarkChange> = hashes
+        .into_iter()
+        .filter(|h| index.remove(h).is_some())
+        .map(|hash| BookmarkChange::Remove { hash })
+        .collect();
+    record_changes(&app, &index, &changes)?;
+    Ok(changes.len())
+}
+
+/// Change the colour of an existing global bookmark
+#[tauri::command]
+pub fn update_global_bookmark(
+    app: AppHandle<Wry>,
+    store: State<'_, GlobalBookmarks>,
+    hash: String,
+    color: Option<String>,
+) -> Result<Bookmark, String> {
+    let mut index = store.0.lock().unwrap();
+    let mut bookmark = index
+        .get(&hash)
+        .cloned()
+        .ok_or_else(|| format!("No global bookmark for hash {}", hash))?;
+    bookmark.color = color;
+    index.upsert(bookmark.clone());
+    let change = BookmarkChange::Upsert {
+        bookmark: bookmark.clone(),
+    };
+    record_changes(&app, &index, &[change])?;
+    Ok(bookmark)
+}
@@ -0,0 +1,240 @@
+use crate::historycommands::parent_folder;
+use crate::models::settings::PickConstraint;
+use crate::models::{AppStateData, FileEntry, HistoryEntry};
+use std::collections::HashMap;
+use tauri_plugin_dialog::FilePath;
+
+fn extension(path: &FilePath) -> Option<String> {
+    match path {
+        FilePath::Path(p) => p.extension().map(|e| e.to_string_lossy().to_lowercase()),
+        FilePath::Url(_) => None,
+    }
+}
+
+/// What the constraints need to know about the session's picks so far
+struct SessionContext<'a> {
+    /// Randomiser picks in the session, oldest first
+    picks: Vec<&'a HistoryEntry>,
+    folder_counts: HashMap<String, usize>,
+}
+
+impl<'a> SessionContext<'a> {
+    /// `session_id` is None when the pick will start a new session
+    fn new(data: &'a AppStateData, session_id: Option<&str>) -> Self {
+        let picks: Vec<&HistoryEntry> = match session_id {
+            Some(id) => data
+                .history
+                .iter()
+                .filter(|h| h.session_id.as_deref() == Some(id))
+                .collect(),
+            None => vec![],
+        };
+        let mut folder_counts = HashMap::new();
+        for pick in &picks {
+            *folder_counts.entry(parent_folder(&pick.path)).or_insert(0) += 1;
+        }
+        SessionContext {
+            picks,
+            folder_counts,
+        }
+    }
+
+    /// Whether picking `file` next keeps to `constraint`
+    fn allows(&self, constraint: &PickConstraint, file: &FileEntry) -> bool {
+        match constraint {
+            PickConstraint::MaxPerFolder { max } => {
+                let count = self
+                    .folder_counts
+                    .get(&parent_folder(&file.path))
+                    .copied()
+                    .unwrap_or(0);
+                count < *max as usize
+            }
+            PickConstraint::ColorEvery { color, every } => {
+                let window = (*every as usize).saturating_sub(1);
+                if self.picks.len() < window {
+                    return true;
+                }
+                let has_color =
+                    |c: Option<&String>| c.is_some_and(|c| c.eq_ignore_ascii_case(color));
+                let recent_has_color = self.picks[self.picks.len() - window..].iter().any(|h| {
+                    has_color(
+                        h.diagnostics
+                            .as_ref()
+                            .and_then(|d| d.chosen_bookmark_color.as_ref()),
+                    )
+                });
+                let file_color = file.bookmark.as_ref().and_then(|b| b.color.as_ref());
+                recent_has_color || has_color(file_color)
+            }
+            PickConstraint::NoRepeatExtension => match self.picks.last() {
+                Some(last) => extension(&last.path) != extension(&file.path),
+                None => true,
+            },
+        }
+    }
+}
+
+/// The constraints enforced for one pick, and those that had to be relaxed
+pub(crate) struct ConstraintCheck<'a> {
+    context: SessionContext<'a>,
+    pub applied: u32,
+    relaxed: Vec<&'a PickConstraint>,
+}
+
+impl ConstraintCheck<'_> {
+    /// How many relaxed constraints picking `file` breaks
+    pub fn violations(&self, file: &FileEntry) -> u32 {
+        self.relaxed
+            .iter()
+            .filter(|c| !self.context.allows(c, file))
+            .count() as u32
+    }
+}
+
+/// Zero the weight of every candidate the session constraints rule out,
+/// applying them in order. A constraint that would leave nothing pickable is
+/// relaxed rather than enforced, so a pick is always possible.
+pub(crate) fn enforce_constraints<'a>(
+    candidates: &[(usize, &FileEntry)],
+    weights: &mut [f64],
+    data: &'a AppStateData,
+    constraints: &'a [PickConstraint],
+    session_id: Option<&str>,
+) -> ConstraintCheck<'a> {
+    let context = SessionContext::new(data, session_id);
+    let mut applied = 0;
+    let mut relaxed = Vec::new();
+    for constraint in constraints {
+        let allowed: Vec<bool> = candidates
+            .iter()
+            .map(|(_, f)| context.allows(constraint, f))
+            .collect();
+        let pickable = weights
+            .iter()
+            .zip(&allowed)
+            .any(|(w, allowed)| *allowed && *w > 0.0);
+        if !pickable {
+            relaxed.push(constraint);
+            continue;
+        }
+        for (w, allowed) in weights.iter_mut().zip(&allowed) {
+            if !allowed {
+                *w = 0.0;
+            }
+        }
+        applied += 1;
+    }
+    ConstraintCheck {
+        context,
+        applied,
+        relaxed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::BookmarkInfo;
+
+    fn file(id: u64, path: &str, color: Option<&str>) -> FileEntry {
+        FileEntry {
+            id,
+            name: path.rsplit('/').next().unwrap().to_string(),
+            path: FilePath::Path(path.into()),
+            excluded: false,
+            hash: None,
+            bookmark: color.map(|c| BookmarkInfo {
+                color: Some(c.to_string()),
+                is_global: false,
+            }),
+            first_seen: None,
+            modified: None,
+        }
+    }
+
+    fn with_picks(paths: &[&str], session_id: &str) -> AppStateData {
+        let history = paths
+            .iter()
+            .map(|path| HistoryEntry {
+                id: 0,
+                name: String::new(),
+                path: FilePath::Path((*path).into()),
+                opened_at: chrono::Utc::now(),
+                diagnostics: None,
+                session_id: Some(session_id.to_string()),
+            })
+            .collect();
+        AppStateData {
+            history,
+            ..Default::default()
+        }
+    }
+
+    fn enforce(
+        files: &[FileEntry],
+        data: &AppStateData,
+        constraints: &[PickConstraint],
+        session_id: Option<&str>,
+    ) -> (Vec<f64>, u32, Vec<u32>) {
+        let candidates: Vec<(usize, &FileEntry)> = files.iter().enumerate().collect();
+        let mut weights = vec![1.0; files.len()];
+        let check = enforce_constraints(&candidates, &mut weights, data, constraints, session_id);
+        let violations = files.iter().map(|f| check.violations(f)).collect();
+        (weights, check.applied, violations)
+    }
+
+    #[test]
+    fn max_per_folder_rules_out_full_folders() {
+        let files = [file(1, "/a/3.png", None), file(2, "/b/1.png", None)];
+        let data = with_picks(&["/a/1.png", "/a/2.png"], "s");
+        let constraints = [PickConstraint::MaxPerFolder { max: 2 }];
+
+        let (weights, applied, _) = enforce(&files, &data, &constraints, Some("s"));
+        assert_eq!(weights, [0.0, 1.0]);
+        assert_eq!(applied, 1);
+
+        // Picks from another session don't count
+        let (weights, _, _) = enforce(&files, &data, &constraints, None);
+        assert_eq!(weights, [1.0, 1.0]);
+    }
+
+    #[test]
+    fn color_every_requires_the_color_once_the_window_is_uncoloured() {
+        let files = [file(1, "/a/1.png", Some("Red")), file(2, "/a/2.png", None)];
+        let data = with_picks(&["/a/0.png"], "s");
+        let constraints = [PickConstraint::ColorEvery {
+            color: "red".into(),
+            every: 2,
+        }];
+        let (weights, applied, _) = enforce(&files, &data, &constraints, Some("s"));
+        assert_eq!(weights, [1.0, 0.0]);
+        assert_eq!(applied, 1);
+    }
+
+    #[test]
+    fn constraints_that_leave_nothing_are_relaxed() {
+        let files = [file(1, "/a/1.png", None), file(2, "/b/2.png", None)];
+        let data = with_picks(&["/c/0.png"], "s");
+        let constraints = [PickConstraint::NoRepeatExtension];
+        let (weights, applied, violations) = enforce(&files, &data, &constraints, Some("s"));
+        assert_eq!(weights, [1.0, 1.0]);
+        assert_eq!(applied, 0);
+        assert_eq!(violations, [1, 1]);
+    }
+
+    #[test]
+    fn later_constraints_see_earlier_exclusions() {
+        let files = [file(1, "/a/1.jpg", None), file(2, "/b/2.png", None)];
+        let data = with_picks(&["/b/0.jpg"], "s");
+        let constraints = [
+            PickConstraint::MaxPerFolder { max: 1 },
+            PickConstraint::NoRepeatExtension,
+        ];
+        // The folder limit leaves only the .jpg, so the extension rule is relaxed
+        let (weights, applied, violations) = enforce(&files, &data, &constraints, Some("s"));
+        assert_eq!(weights, [1.0, 0.0]);
+        assert_eq!(applied, 1);
+        assert_eq!(violations, [1, 0]);
+    }
+}
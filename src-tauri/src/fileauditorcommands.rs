@@ -1,4 +1,7 @@
 use crate::models::common::hash_from_meta;
+use crate::models::settings::OpenAction;
+use crate::openactions::{resolve_open_action, run_open_action};
+use crate::setting_commands::get_app_settings;
 use chrono::DateTime;
 use ignore::WalkBuilder;
 use serde::{Deserialize, Serialize};
@@ -92,7 +95,7 @@ pub async fn audit_list_files(path: String) -> Result<Vec<AuditFileEntry>, Strin
             });
         }
 
-        results.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        results.sort_by_key(|a| a.name.to_lowercase());
         results
     })
     .await
@@ -100,7 +103,16 @@ pub async fn audit_list_files(path: String) -> Result<Vec<AuditFileEntry>, Strin
 }
 
 #[tauri::command]
+// Process handles are only tracked on Windows
+#[cfg_attr(not(target_os = "windows"), allow(unused_variables))]
 pub fn open_audit_file(app: tauri::AppHandle, path: String, track: bool) -> Result<(), String> {
+    // A configured open action replaces the default open and is not tracked
+    let settings = get_app_settings(app.clone())?;
+    let action = resolve_open_action(&settings, &path, None);
+    if action != OpenAction::Default {
+        return run_open_action(&app, &action, &path, false).map(|_| ());
+    }
+
     #[cfg(target_os = "windows")]
     if track {
         use windows::core::PCWSTR;
@@ -161,6 +173,8 @@ pub fn forget_tracked_file(app: tauri::AppHandle, path: String) -> Result<(), St
 /// Remove from map and kill the viewer process.
 /// Used when navigating — close current file before opening the next.
 #[tauri::command]
+// Process handles are only tracked on Windows
+#[cfg_attr(not(target_os = "windows"), allow(unused_variables))]
 pub fn close_tracked_file(app: tauri::AppHandle, path: String) -> Result<(), String> {
     let map = app.state::<TrackedProcessMap>();
     let handle_raw = map.0.lock().unwrap().remove(&path);
@@ -177,6 +191,8 @@ pub fn close_tracked_file(app: tauri::AppHandle, path: String) -> Result<(), Str
 }
 
 #[tauri::command]
+// Process handles are only tracked on Windows
+#[cfg_attr(not(target_os = "windows"), allow(unused_variables))]
 pub fn delete_to_trash(app: tauri::AppHandle, path: String) -> Result<(), String> {
     #[cfg(target_os = "windows")]
     {
@@ -1,18 +1,34 @@
+use crate::blendcommands::LoadedBlend;
+use crate::bookmarkcommands::GlobalBookmarks;
+use crate::constraints::enforce_constraints;
+use crate::historycommands::path_key;
+use crate::historylog::{append_history, rewrite_history};
+use crate::migrations::{SchemaKind, STATS_SCHEMA_VERSION};
 use crate::models::common::hash_from_meta;
+use crate::models::settings::{AppSettings, OpenAction, PickMode};
 use crate::models::{
-    AppStateData, Bookmark, FileEntry, FileScore, FilterMatchType, FilterRule, HistoryEntry,
-    PersistedStats, SavedPath,
+    AppStateData, Bookmark, BookmarkIndex, CrawlProgress, FileEntry, FileScore, FilterMatchType,
+    FilterRule, HistoryEntry, PathVariables, PersistedStats, PickDiagnostics, PickExplanation,
+    PickFactors, SavedPath,
+};
+use crate::models::FilterAction;
+#[cfg(target_os = "linux")]
+use crate::openactions::default_handler_command;
+use crate::openactions::{resolve_open_action, run_open_action};
+use crate::persistence::{load_with_recovery, quarantine, save_with_backups};
+use crate::seriescommands::choose_series_file;
+use crate::sessioncommands::{
+    active_preset_id, is_open_session, record_pick, undo_pick, PickSessions, SessionUndo,
 };
-use crate::models::{FilterAction, RandomiserPreset};
 use crate::setting_commands::get_app_settings;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use ignore::WalkBuilder;
 use rand::distr::weighted::WeightedIndex;
 use rand::prelude::*;
 use rayon::prelude::*;
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 use tauri::Emitter;
 use tauri::Manager;
 use tauri::State;
@@ -21,13 +37,51 @@ use tauri_plugin_opener::OpenerExt;
 
 pub struct PathPickCounts(pub Mutex<HashMap<String, u32>>);
 
-fn stats_file_path(app: &tauri::AppHandle) -> Option<std::path::PathBuf> {
+/// When each path was first found by a crawl, kept across crawls and restarts
+pub struct PathFirstSeen(pub Mutex<HashMap<String, DateTime<Utc>>>);
+
+/// Oldest picks are dropped from the undo stack beyond this many
+const MAX_PICK_UNDO: usize = 50;
+
+/// What one pick changed, so `undo_last_pick` can put it back
+pub struct PickUndo {
+    file_id: u64,
+    opened_at: DateTime<Utc>,
+    previous_last_picked_id: Option<u64>,
+    /// Recency entries trimmed from the front to make room for the pick
+    recency_trimmed: Vec<u64>,
+    session: SessionUndo,
+}
+
+/// Picks that can still be undone, newest last. Runtime only, and cleared
+/// whenever a crawl reassigns file IDs.
+pub struct PickUndoStack(pub Mutex<Vec<PickUndo>>);
+
+pub(crate) fn stats_file_path(app: &tauri::AppHandle) -> Option<std::path::PathBuf> {
     app.path()
         .app_data_dir()
         .ok()
         .map(|d| d.join("randomiser_stats.json"))
 }
 
+/// Read randomiser_stats.json, migrating it to the current schema if needed
+/// and falling back to the newest valid backup if it is damaged. Returns None
+/// when there is no stats file yet or nothing could be read.
+pub fn load_persisted_stats(app: &tauri::AppHandle) -> Option<PersistedStats> {
+    let path = stats_file_path(app)?;
+    match load_with_recovery::<PersistedStats>(&path, SchemaKind::Stats) {
+        Ok(stats) => stats,
+        Err(e) => {
+            // Keep the unreadable file out of the backup rotation
+            eprintln!("Failed to load randomiser stats: {}", e);
+            if let Ok(moved) = quarantine(&path) {
+                eprintln!("Moved unreadable stats to {:?}", moved);
+            }
+            None
+        }
+    }
+}
+
 pub fn prune_history(history: &mut Vec<crate::models::HistoryEntry>, retention_days: u32) {
     if retention_days == 0 {
         return;
@@ -36,37 +90,70 @@ pub fn prune_history(history: &mut Vec<crate::models::HistoryEntry>, retention_d
     history.retain(|h| h.opened_at >= cutoff);
 }
 
-fn save_persisted_stats(app: &tauri::AppHandle, data: &AppStateData) {
+/// Set while a deferred stats save is waiting to run
+pub struct StatsSavePending(pub AtomicBool);
+
+/// A pick is in history.jsonl as soon as it happens. The stats file holds
+/// counts for the whole library, so picks write it at most once per delay.
+pub(crate) const STATS_SAVE_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Save the stats after `STATS_SAVE_DELAY`, unless a save is already waiting
+pub(crate) fn schedule_stats_save(app: &tauri::AppHandle) {
+    let pending = app.state::<StatsSavePending>();
+    if pending.0.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(STATS_SAVE_DELAY);
+        flush_stats_save(&app);
+    });
+}
+
+/// Run a waiting deferred stats save now. Called on exit so nothing is lost.
+pub fn flush_stats_save(app: &tauri::AppHandle) {
+    let pending = app.state::<StatsSavePending>();
+    if !pending.0.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let app_data = app.state::<Mutex<AppStateData>>();
+    let data = app_data.lock().unwrap();
+    save_persisted_stats(app, &data);
+}
+
+/// Persist pick counts and the recency list. History is appended to its own
+/// log as entries are recorded rather than rewritten here. Counts for the
+/// crawled files are merged into `PathPickCounts`, so files outside the
+/// current crawl keep theirs.
+pub(crate) fn save_persisted_stats(app: &tauri::AppHandle, data: &AppStateData) {
     let Some(path) = stats_file_path(app) else {
         return;
     };
-    let settings = get_app_settings(app.clone()).ok();
-    let retention_days = settings
-        .as_ref()
-        .map(|s| s.file_randomiser.history_retention_days)
-        .unwrap_or(180);
-    let persist_recency = settings
-        .as_ref()
+    let persist_recency = get_app_settings(app.clone())
+        .ok()
         .map(|s| s.file_randomiser.persist_recency)
         .unwrap_or(false);
 
-    let mut history = data.history.clone();
-    prune_history(&mut history, retention_days);
-    let path_pick_counts: HashMap<String, u32> = data
-        .files
-        .iter()
-        .filter_map(|f| {
-            let count = *data.pick_counts.get(&f.id)?;
-            if count == 0 {
-                return None;
-            }
+    let path_pick_counts: HashMap<String, u32> = {
+        let counts_state = app.state::<PathPickCounts>();
+        let mut counts = counts_state.0.lock().unwrap();
+        for f in &data.files {
             let path_str = match &f.path {
                 FilePath::Path(p) => p.to_string_lossy().to_string(),
                 FilePath::Url(u) => u.to_string(),
             };
-            Some((path_str, count))
-        })
-        .collect();
+            match data.pick_counts.get(&f.id) {
+                Some(&count) if count > 0 => {
+                    counts.insert(path_str, count);
+                }
+                _ => {
+                    counts.remove(&path_str);
+                }
+            }
+        }
+        counts.clone()
+    };
+    let path_first_seen = app.state::<PathFirstSeen>().0.lock().unwrap().clone();
 
     let recency_list_paths: Vec<String> = if persist_recency {
         data.recency_list
@@ -82,12 +169,17 @@ fn save_persisted_stats(app: &tauri::AppHandle, data: &AppStateData) {
     };
 
     let stats = PersistedStats {
-        history,
+        schema_version: STATS_SCHEMA_VERSION,
+        history: vec![],
         path_pick_counts,
         recency_list_paths,
+        path_first_seen,
     };
-    if let Ok(json) = serde_json::to_string_pretty(&stats) {
-        let _ = std::fs::write(path, json);
+    let saved = serde_json::to_string_pretty(&stats)
+        .map_err(|e| e.to_string())
+        .and_then(|json| save_with_backups(&path, json.as_bytes()));
+    if let Err(e) = saved {
+        eprintln!("Failed to save randomiser stats: {}", e);
     }
 }
 
@@ -161,181 +253,329 @@ pub fn remove_path(app_data: State<'_, Mutex<AppStateData>>, id: u64) -> bool {
     original_len != data.paths.len()
 }
 
+/// Cancel flag of the crawl currently running, if any
+pub struct CrawlJob(pub Mutex<Option<Arc<AtomicBool>>>);
+
+/// Emit "crawl-progress" after this many directories and files
+const CRAWL_PROGRESS_EVERY: u64 = 500;
+
+/// Counts what a crawl has walked, reports it and carries its cancel flag
+pub(crate) struct CrawlMonitor {
+    app: tauri::AppHandle,
+    cancel: Arc<AtomicBool>,
+    directories: AtomicU64,
+    files: AtomicU64,
+    /// Directories and files together, which decides when to emit
+    total: AtomicU64,
+}
+
+impl CrawlMonitor {
+    /// A monitor that emits progress events through `app`
+    pub(crate) fn new(app: tauri::AppHandle, cancel: Arc<AtomicBool>) -> Self {
+        CrawlMonitor {
+            app,
+            cancel,
+            directories: AtomicU64::new(0),
+            files: AtomicU64::new(0),
+            total: AtomicU64::new(0),
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn progress(&self) -> CrawlProgress {
+        CrawlProgress {
+            directories: self.directories.load(Ordering::Relaxed),
+            files: self.files.load(Ordering::Relaxed),
+        }
+    }
+
+    fn saw(&self, is_dir: bool) {
+        let counter = if is_dir {
+            &self.directories
+        } else {
+            &self.files
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        // Exactly one walker thread sees each multiple
+        let total = self.total.fetch_add(1, Ordering::Relaxed) + 1;
+        if total.is_multiple_of(CRAWL_PROGRESS_EVERY) {
+            self.emit(self.progress());
+        }
+    }
+
+    pub(crate) fn emit(&self, progress: CrawlProgress) {
+        let _ = self.app.emit("crawl-progress", progress);
+    }
+}
+
+/// Register a new crawl as the running one, cancelling any crawl it replaces.
+/// Returns its cancel flag.
+pub(crate) fn start_crawl_job(app: &tauri::AppHandle) -> Arc<AtomicBool> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    let job = app.state::<CrawlJob>();
+    if let Some(previous) = job.0.lock().unwrap().replace(Arc::clone(&cancel)) {
+        previous.store(true, Ordering::Relaxed);
+    }
+    cancel
+}
+
+/// Whether a finished crawl may install its result, i.e. it was neither
+/// cancelled nor superseded, and mark it done. Call while holding the locks
+/// the result is written under, so a crawl started meanwhile cannot be
+/// overwritten by this one.
+pub(crate) fn finish_crawl_job(app: &tauri::AppHandle, cancel: &Arc<AtomicBool>) -> bool {
+    let job = app.state::<CrawlJob>();
+    let mut current = job.0.lock().unwrap();
+    if cancel.load(Ordering::Relaxed) {
+        return false;
+    }
+    if current.as_ref().is_some_and(|c| Arc::ptr_eq(c, cancel)) {
+        *current = None;
+    }
+    true
+}
+
+/// Crawl the saved paths in the background, emitting "crawl-progress" as it
+/// goes. The file list is only replaced once the walk finishes, so other
+/// commands keep working on the previous list meanwhile. Returns None when
+/// the crawl was cancelled or superseded by a newer one.
 #[tauri::command]
-pub fn crawl_paths(
+pub async fn crawl_paths(
     app: tauri::AppHandle,
-    app_data: State<'_, Mutex<AppStateData>>,
-    path_pick_counts: State<'_, PathPickCounts>,
-    global_bookmarks: Vec<Bookmark>,
     local_bookmarks: Vec<Bookmark>,
-) -> Vec<FileEntry> {
+) -> Result<Option<Vec<FileEntry>>, String> {
+    let cancel = start_crawl_job(&app);
+
+    let settings = get_app_settings(app.clone()).unwrap_or_default();
+    let path_variables = resolve_path_variables(&app, &settings);
+    let (paths, filter_rules) = {
+        let app_data = app.state::<Mutex<AppStateData>>();
+        let data = app_data.lock().unwrap();
+        (
+            path_variables.expand_saved_paths(&data.paths),
+            data.filter_rules.clone(),
+        )
+    };
+    let global_bookmarks = app.state::<GlobalBookmarks>().0.lock().unwrap().clone();
+    let local_bookmarks = BookmarkIndex::from(local_bookmarks);
+
+    let monitor = CrawlMonitor::new(app.clone(), Arc::clone(&cancel));
+    let files = tauri::async_runtime::spawn_blocking(move || {
+        let files = crawl_files(
+            &paths,
+            &filter_rules,
+            &global_bookmarks,
+            &local_bookmarks,
+            &AtomicU64::new(1),
+            &monitor,
+        );
+        monitor.emit(monitor.progress());
+        files
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Blend before app data, as in load_blend
+    let loaded_blend = app.state::<LoadedBlend>();
+    let mut blend_session = loaded_blend.0.lock().unwrap();
+    let app_data = app.state::<Mutex<AppStateData>>();
     let mut data = app_data.lock().unwrap();
-    data.files.clear();
-
-    let paths = data.paths.clone();
-    let filter_rules = data.filter_rules.clone();
-    let next_id = AtomicU64::new(1);
-
-    fn matches_rule(
-        path: &str,
-        hash_val: u64,
-        rule: &FilterRule,
-        global: &[Bookmark],
-        local: &[Bookmark],
-    ) -> bool {
-        let hash_str = format!("{:x}", hash_val);
-
-        if let FilterMatchType::Bookmarks = rule.match_type {
-            let pattern_lower = rule.pattern.trim().to_lowercase();
-            let raw = pattern_lower.strip_prefix("@bookmarks").unwrap_or("");
-
-            let mut scope: Option<&str> = None;
-            let mut colors: Vec<String> = Vec::new();
-
-            let parts: Vec<&str> = raw.split(':').filter(|p| !p.is_empty()).collect();
-
-            for part in parts {
-                match part {
-                    "global" | "nonglobal" => scope = Some(part),
-                    _ => {
-                        colors = part
-                            .split(',')
-                            .map(|c| match c.trim() {
-                                "gold" | "yellow" => "#ffd700",
-                                "red" => "#ff6b6b",
-                                "green" => "#6bcb77",
-                                "blue" => "#4d96ff",
-                                other => other,
-                            })
-                            .map(|c| c.to_string())
-                            .collect();
-                    }
+    let Some(files) = files.filter(|_| finish_crawl_job(&app, &cancel)) else {
+        return Ok(None);
+    };
+
+    // A fresh crawl reassigns file IDs, so any loaded blend's pools are stale
+    *blend_session = None;
+    data.path_variables = path_variables;
+    data.files = files;
+    remap_persisted_state(&app, &mut data, &app.state::<PathPickCounts>());
+
+    Ok(Some(data.files.clone()))
+}
+
+/// Stop the running crawl, keeping the current file list. Returns whether a
+/// crawl was running.
+#[tauri::command]
+pub fn cancel_crawl(job: State<'_, CrawlJob>) -> bool {
+    match job.0.lock().unwrap().take() {
+        Some(cancel) => {
+            cancel.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+fn matches_rule(
+    path: &str,
+    hash_val: u64,
+    rule: &FilterRule,
+    global: &BookmarkIndex,
+    local: &BookmarkIndex,
+) -> bool {
+    let hash_str = format!("{:x}", hash_val);
+
+    if let FilterMatchType::Bookmarks = rule.match_type {
+        let pattern_lower = rule.pattern.trim().to_lowercase();
+        let raw = pattern_lower.strip_prefix("@bookmarks").unwrap_or("");
+
+        let mut scope: Option<&str> = None;
+        let mut colors: Vec<String> = Vec::new();
+
+        let parts: Vec<&str> = raw.split(':').filter(|p| !p.is_empty()).collect();
+
+        for part in parts {
+            match part {
+                "global" | "nonglobal" => scope = Some(part),
+                _ => {
+                    colors = part
+                        .split(',')
+                        .map(|c| match c.trim() {
+                            "gold" | "yellow" => "#ffd700",
+                            "red" => "#ff6b6b",
+                            "green" => "#6bcb77",
+                            "blue" => "#4d96ff",
+                            other => other,
+                        })
+                        .map(|c| c.to_string())
+                        .collect();
                 }
             }
+        }
 
-            let matches_list = |list: &[Bookmark]| {
-                list.iter().any(|bm| {
-                    if bm.hash.to_lowercase() != hash_str {
-                        return false;
-                    }
-                    if colors.is_empty() {
-                        return true;
-                    }
-                    bm.color
-                        .as_ref()
-                        .map(|c| colors.iter().any(|t| t.eq_ignore_ascii_case(c)))
-                        .unwrap_or(false)
-                })
-            };
+        let matches_list = |index: &BookmarkIndex| {
+            index.get(&hash_str).is_some_and(|bm| {
+                if colors.is_empty() {
+                    return true;
+                }
+                bm.color
+                    .as_ref()
+                    .map(|c| colors.iter().any(|t| t.eq_ignore_ascii_case(c)))
+                    .unwrap_or(false)
+            })
+        };
 
-            match scope {
-                Some("global") => matches_list(global),
-                Some("nonglobal") => matches_list(local),
-                _ => matches_list(global) || matches_list(local),
-            }
+        match scope {
+            Some("global") => matches_list(global),
+            Some("nonglobal") => matches_list(local),
+            _ => matches_list(global) || matches_list(local),
+        }
+    } else {
+        let text = if rule.case_sensitive {
+            path.to_string()
         } else {
-            let text = if rule.case_sensitive {
-                path.to_string()
-            } else {
-                path.to_lowercase()
-            };
+            path.to_lowercase()
+        };
 
-            let pattern = if rule.case_sensitive {
-                rule.pattern.clone()
-            } else {
-                rule.pattern.to_lowercase()
-            };
+        let pattern = if rule.case_sensitive {
+            rule.pattern.clone()
+        } else {
+            rule.pattern.to_lowercase()
+        };
 
-            match rule.match_type {
-                FilterMatchType::Contains => text.contains(&pattern),
-                FilterMatchType::StartsWith => text.starts_with(&pattern),
-                FilterMatchType::EndsWith => text.ends_with(&pattern),
-                FilterMatchType::Regex => regex::Regex::new(&rule.pattern)
-                    .map(|r| r.is_match(path))
-                    .unwrap_or(false),
-                _ => false,
-            }
+        match rule.match_type {
+            FilterMatchType::Contains => text.contains(&pattern),
+            FilterMatchType::StartsWith => text.starts_with(&pattern),
+            FilterMatchType::EndsWith => text.ends_with(&pattern),
+            FilterMatchType::Regex => regex::Regex::new(&rule.pattern)
+                .map(|r| r.is_match(path))
+                .unwrap_or(false),
+            _ => false,
         }
     }
+}
 
-    fn should_include(
-        path: &std::path::Path,
-        hash_val: u64,
-        rules: &[FilterRule],
-        global: &[Bookmark],
-        local: &[Bookmark],
-    ) -> bool {
-        let s = path.to_string_lossy();
-        rules.iter().any(|r| {
-            matches_rule(&s, hash_val, r, global, local)
-                && matches!(r.action, FilterAction::Include)
-        })
+fn should_include(
+    path: &std::path::Path,
+    hash_val: u64,
+    rules: &[FilterRule],
+    global: &BookmarkIndex,
+    local: &BookmarkIndex,
+) -> bool {
+    let s = path.to_string_lossy();
+    rules.iter().any(|r| {
+        matches_rule(&s, hash_val, r, global, local) && matches!(r.action, FilterAction::Include)
+    })
+}
+
+fn should_exclude(
+    path: &std::path::Path,
+    hash_val: u64,
+    rules: &[FilterRule],
+    global: &BookmarkIndex,
+    local: &BookmarkIndex,
+) -> bool {
+    let s = path.to_string_lossy();
+    rules.iter().any(|r| {
+        matches_rule(&s, hash_val, r, global, local) && matches!(r.action, FilterAction::Exclude)
+    })
+}
+
+fn resolve_bookmark(
+    hash: &str,
+    global: &BookmarkIndex,
+    local: &BookmarkIndex,
+) -> Option<crate::models::BookmarkInfo> {
+    // Local overrides global
+    if let Some(bm) = local.get(hash) {
+        return Some(crate::models::BookmarkInfo {
+            color: bm.color.clone(),
+            is_global: false,
+        });
     }
 
-    fn should_exclude(
-        path: &std::path::Path,
-        hash_val: u64,
-        rules: &[FilterRule],
-        global: &[Bookmark],
-        local: &[Bookmark],
-    ) -> bool {
-        let s = path.to_string_lossy();
-        rules.iter().any(|r| {
-            matches_rule(&s, hash_val, r, global, local)
-                && matches!(r.action, FilterAction::Exclude)
-        })
+    if let Some(bm) = global.get(hash) {
+        return Some(crate::models::BookmarkInfo {
+            color: bm.color.clone(),
+            is_global: true,
+        });
     }
 
-    fn resolve_bookmark(
-        hash: &str,
-        global: &[Bookmark],
-        local: &[Bookmark],
-    ) -> Option<crate::models::BookmarkInfo> {
-        // Local overrides global
-        if let Some(bm) = local.iter().find(|b| b.hash.eq_ignore_ascii_case(hash)) {
-            return Some(crate::models::BookmarkInfo {
-                color: bm.color.clone(),
-                is_global: false,
-            });
-        }
+    None
+}
 
-        if let Some(bm) = global.iter().find(|b| b.hash.eq_ignore_ascii_case(hash)) {
-            return Some(crate::models::BookmarkInfo {
-                color: bm.color.clone(),
-                is_global: true,
-            });
+fn is_system_file(path: &std::path::Path) -> bool {
+    if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+        // Unix hidden files
+        if name.starts_with('.') {
+            return true;
         }
-
-        None
     }
 
-    fn is_system_file(path: &std::path::Path) -> bool {
-        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-            // Unix hidden files
-            if name.starts_with('.') {
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        if let Ok(meta) = path.metadata() {
+            const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+            if meta.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0 {
                 return true;
             }
         }
-
-        #[cfg(windows)]
-        {
-            use std::os::windows::fs::MetadataExt;
-            if let Ok(meta) = path.metadata() {
-                const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
-                if meta.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0 {
-                    return true;
-                }
-            }
-        }
-
-        false
     }
 
+    false
+}
+
+/// Walk `paths` and build file entries, applying filter rules and resolving
+/// bookmarks (local overrides global). IDs are drawn from `next_id`. Returns
+/// None if `monitor` was cancelled during the walk.
+pub(crate) fn crawl_files(
+    paths: &[SavedPath],
+    filter_rules: &[FilterRule],
+    global_bookmarks: &BookmarkIndex,
+    local_bookmarks: &BookmarkIndex,
+    next_id: &AtomicU64,
+    monitor: &CrawlMonitor,
+) -> Option<Vec<FileEntry>> {
     // Collect all files
-    // Collect (path, hash) together so metadata is only read once
-    let all_files: Arc<Mutex<Vec<(std::path::PathBuf, u64)>>> = Arc::new(Mutex::new(Vec::new()));
+    // Collect (path, hash, mtime) together so metadata is only read once
+    type Collected = (std::path::PathBuf, u64, Option<DateTime<Utc>>);
+    let all_files: Arc<Mutex<Vec<Collected>>> = Arc::new(Mutex::new(Vec::new()));
 
-    for saved_path in &paths {
+    for saved_path in paths {
         if let Some(p) = saved_path.path.as_path() {
             if p.is_dir() {
                 let collected = Arc::clone(&all_files);
@@ -347,13 +587,18 @@ pub fn crawl_paths(
                         let collected = Arc::clone(&collected);
                         Box::new(move |entry| {
                             use ignore::WalkState;
+                            if monitor.is_cancelled() {
+                                return WalkState::Quit;
+                            }
                             if let Ok(e) = entry {
                                 if let Ok(meta) = e.metadata() {
+                                    monitor.saw(meta.is_dir());
                                     if meta.is_file() {
-                                        collected
-                                            .lock()
-                                            .unwrap()
-                                            .push((e.path().to_path_buf(), hash_from_meta(&meta)));
+                                        collected.lock().unwrap().push((
+                                            e.path().to_path_buf(),
+                                            hash_from_meta(&meta),
+                                            meta.modified().ok().map(DateTime::<Utc>::from),
+                                        ));
                                     }
                                 }
                             }
@@ -364,12 +609,15 @@ pub fn crawl_paths(
         }
     }
 
+    if monitor.is_cancelled() {
+        return None;
+    }
     let all_files = Arc::try_unwrap(all_files).unwrap().into_inner().unwrap();
 
     // Parallel hashing & FileEntry construction
     let file_entries: Vec<FileEntry> = all_files
         .into_par_iter()
-        .map(|(path, hash_val)| {
+        .map(|(path, hash_val, modified)| {
             // <-- destructure the tuple
             let id = next_id.fetch_add(1, Ordering::SeqCst);
             let name = path
@@ -381,9 +629,9 @@ pub fn crawl_paths(
             let included = should_include(
                 &path,
                 hash_val,
-                &filter_rules,
-                &global_bookmarks,
-                &local_bookmarks,
+                filter_rules,
+                global_bookmarks,
+                local_bookmarks,
             );
             let excluded = if included {
                 false
@@ -391,15 +639,15 @@ pub fn crawl_paths(
                 should_exclude(
                     &path,
                     hash_val,
-                    &filter_rules,
-                    &global_bookmarks,
-                    &local_bookmarks,
+                    filter_rules,
+                    global_bookmarks,
+                    local_bookmarks,
                 )
             };
 
             let hash_string = format!("{:x}", hash_val);
 
-            let bookmark = resolve_bookmark(&hash_string, &global_bookmarks, &local_bookmarks);
+            let bookmark = resolve_bookmark(&hash_string, global_bookmarks, local_bookmarks);
 
             FileEntry {
                 id,
@@ -408,11 +656,26 @@ pub fn crawl_paths(
                 excluded,
                 hash: Some(hash_string),
                 bookmark,
+                first_seen: None,
+                modified,
             }
         })
         .collect();
 
-    data.files = file_entries;
+    Some(file_entries)
+}
+
+/// Map persisted path-keyed pick counts (and the recency list, when enabled)
+/// onto the IDs of the freshly crawled `data.files`, and stamp each file with
+/// when it was first seen. Paths no crawl has found before are recorded as
+/// first seen now.
+pub(crate) fn remap_persisted_state(
+    app: &tauri::AppHandle,
+    data: &mut AppStateData,
+    path_pick_counts: &PathPickCounts,
+) {
+    // Undo entries hold file IDs from before the remap
+    app.state::<PickUndoStack>().0.lock().unwrap().clear();
 
     // Remap persisted path-keyed pick counts to current file IDs
     let persisted = path_pick_counts.0.lock().unwrap();
@@ -427,48 +690,67 @@ pub fn crawl_paths(
             persisted.get(&path_str).map(|&count| (f.id, count))
         })
         .collect();
+    drop(persisted);
+
+    let first_seen_state = app.state::<PathFirstSeen>();
+    let mut first_seen = first_seen_state.0.lock().unwrap();
+    // Before anything was tracked, every file would look newly arrived; seed
+    // the first crawl with modification times instead
+    let seeding = first_seen.is_empty();
+    let now = Utc::now();
+    let mut recorded = false;
+    for f in data.files.iter_mut() {
+        let seen = *first_seen.entry(path_key(&f.path)).or_insert_with(|| {
+            recorded = true;
+            if seeding {
+                f.modified.unwrap_or(now)
+            } else {
+                now
+            }
+        });
+        f.first_seen = Some(seen);
+    }
+    drop(first_seen);
+    if recorded {
+        save_persisted_stats(app, data);
+    }
 
     // Remap persisted recency list (paths → IDs) when persist_recency is enabled
-    let persist_recency = get_app_settings(app.clone())
-        .ok()
-        .map(|s| s.file_randomiser.persist_recency)
-        .unwrap_or(false);
-    if persist_recency {
-        if let Some(stats_path) = stats_file_path(&app) {
-            if let Ok(content) = std::fs::read_to_string(stats_path) {
-                if let Ok(stats) = serde_json::from_str::<crate::models::PersistedStats>(&content) {
-                    if !stats.recency_list_paths.is_empty() {
-                        let recency_window =
-                            ((data.files.len() as f64).sqrt() * 4.0).clamp(15.0, 200.0) as usize;
-                        let path_to_id: HashMap<String, u64> = data
-                            .files
-                            .iter()
-                            .map(|f| {
-                                let p = match &f.path {
-                                    FilePath::Path(p) => p.to_string_lossy().to_string(),
-                                    FilePath::Url(u) => u.to_string(),
-                                };
-                                (p, f.id)
-                            })
-                            .collect();
-                        let remapped: Vec<u64> = stats
-                            .recency_list_paths
-                            .iter()
-                            .filter_map(|p| path_to_id.get(p).copied())
-                            .collect();
-                        let start = remapped.len().saturating_sub(recency_window * 2);
-                        data.recency_list = remapped[start..].to_vec();
-                    }
-                }
+    let settings = get_app_settings(app.clone()).unwrap_or_default();
+    if settings.file_randomiser.persist_recency {
+        if let Some(stats) = load_persisted_stats(app) {
+            if !stats.recency_list_paths.is_empty() {
+                let recency_window = settings
+                    .file_randomiser
+                    .tuning
+                    .recency_window(data.files.len());
+                let path_to_id: HashMap<String, u64> = data
+                    .files
+                    .iter()
+                    .map(|f| {
+                        let p = match &f.path {
+                            FilePath::Path(p) => p.to_string_lossy().to_string(),
+                            FilePath::Url(u) => u.to_string(),
+                        };
+                        (p, f.id)
+                    })
+                    .collect();
+                let remapped: Vec<u64> = stats
+                    .recency_list_paths
+                    .iter()
+                    .filter_map(|p| path_to_id.get(p).copied())
+                    .collect();
+                let start = remapped.len().saturating_sub(recency_window * 2);
+                data.recency_list = remapped[start..].to_vec();
             }
         }
     }
-
-    data.files.clone()
 }
 
+/// Open `path` in its default application, blocking until the application
+/// exits when `show_cmd` is set. Returns whether it waited.
 #[cfg(target_os = "windows")]
-fn open_and_wait(path: &str, show_cmd: bool) -> std::io::Result<()> {
+fn open_and_wait(path: &str, show_cmd: bool) -> std::io::Result<bool> {
     use std::os::windows::process::CommandExt;
     use std::process::Command;
 
@@ -485,64 +767,100 @@ fn open_and_wait(path: &str, show_cmd: bool) -> std::io::Result<()> {
             .spawn()?;
     }
 
-    Ok(())
+    Ok(show_cmd)
 }
 
 #[cfg(target_os = "macos")]
-fn open_and_wait(path: &str, show_cmd: bool) -> std::io::Result<()> {
+fn open_and_wait(path: &str, show_cmd: bool) -> std::io::Result<bool> {
     if show_cmd {
         std::process::Command::new("open")
             .args(["-W", path])
             .status()
-            .map(|_| ())
+            .map(|_| true)
     } else {
         std::process::Command::new("open")
             .arg(path)
             .spawn()
-            .map(|_| ())
+            .map(|_| false)
     }
 }
 
+/// gio hands the file over and exits at once, so to wait we launch the
+/// default handler from the desktop database ourselves. Falls back to gio,
+/// without waiting, when no handler can be resolved.
 #[cfg(target_os = "linux")]
-fn open_and_wait(path: &str, _show_cmd: bool) -> std::io::Result<()> {
+fn open_and_wait(path: &str, wait: bool) -> std::io::Result<bool> {
+    if wait {
+        if let Some(mut handler) = default_handler_command(path) {
+            handler.status()?;
+            return Ok(true);
+        }
+    }
     std::process::Command::new("gio")
         .args(["open", path])
         .spawn()
-        .map(|_| ())
+        .map(|_| false)
 }
 
+fn record_history(app: &tauri::AppHandle, data: &mut AppStateData, entry: HistoryEntry) {
+    if let Err(e) = append_history(app, &entry) {
+        eprintln!("Failed to append history: {}", e);
+    }
+    data.history.push(entry);
+    schedule_stats_save(app);
+}
+
+/// Open a file with the open action configured for it, recording it in
+/// history when `id` and `name` are given. Randomiser picks are recorded by
+/// `commit_pick` instead.
 pub fn open_file_tracked(
     app: tauri::AppHandle,
     path: String,
     id: Option<u64>,
     name: Option<String>,
-    diagnostics: Option<crate::models::PickDiagnostics>,
 ) -> Result<(), String> {
-    let _settings = get_app_settings(app.clone())?;
-    #[cfg(any(target_os = "windows", target_os = "macos"))]
-    let allow_tracking = _settings.file_randomiser.allow_process_tracking;
-    #[cfg(target_os = "linux")]
-    let allow_tracking = false;
+    let settings = get_app_settings(app.clone())?;
+    let allow_tracking = settings.file_randomiser.allow_process_tracking;
 
     if let (Some(id), Some(name)) = (id, name) {
         let app_data_lock = app.state::<Mutex<AppStateData>>();
         let mut state = app_data_lock.lock().unwrap();
-        state.history.push(HistoryEntry {
-            id,
-            name,
-            path: FilePath::Path(path.clone().into()),
-            opened_at: Utc::now(),
-            diagnostics,
-        });
-        save_persisted_stats(&app, &state);
+        record_history(
+            &app,
+            &mut state,
+            HistoryEntry {
+                id,
+                name,
+                path: FilePath::Path(path.clone().into()),
+                opened_at: Utc::now(),
+                diagnostics: None,
+                session_id: None,
+            },
+        );
     }
 
+    let action = resolve_open_action(&settings, &path, active_preset_id(&app).as_deref());
+
     // Spawn thread to open file
     let app_clone = app.clone();
     std::thread::spawn(move || {
-        let _ = open_and_wait(&path, allow_tracking);
-        if allow_tracking {
-            let _ = app_clone.emit("file-closed", ());
+        let waited = match action {
+            OpenAction::Default => {
+                let waited = open_and_wait(&path, allow_tracking).map_err(|e| e.to_string());
+                // No close will be reported, so let the UI say so
+                if allow_tracking && waited == Ok(false) {
+                    let _ = app_clone.emit("tracking-unavailable", &path);
+                }
+                waited
+            }
+            action => run_open_action(&app_clone, &action, &path, allow_tracking),
+        };
+        match waited {
+            Ok(true) => {
+                let _ = app_clone.emit("file-closed", ());
+            }
+            Ok(false) => {}
+            Err(e) => eprintln!("Failed to open {}: {}", path, e),
         }
     });
 
@@ -581,6 +899,43 @@ fn compute_bookmark_factor(
     }
 }
 
+/// Built-in `${NAME}` roots from the OS plus user-defined ones from settings.
+/// User values may reference the built-ins; user names override built-ins.
+pub(crate) fn resolve_path_variables(
+    app: &tauri::AppHandle,
+    settings: &AppSettings,
+) -> PathVariables {
+    let resolver = app.path();
+    let mut vars = PathVariables::default();
+    let builtins = [
+        ("HOME", resolver.home_dir()),
+        ("PICTURES", resolver.picture_dir()),
+        ("DOCUMENTS", resolver.document_dir()),
+        ("DOWNLOADS", resolver.download_dir()),
+        ("VIDEOS", resolver.video_dir()),
+        ("MUSIC", resolver.audio_dir()),
+        ("DESKTOP", resolver.desktop_dir()),
+    ];
+    for (name, dir) in builtins {
+        if let Ok(dir) = dir {
+            vars.insert(name, dir.to_string_lossy().to_string());
+        }
+    }
+
+    let builtin_vars = vars.clone();
+    for (name, value) in &settings.file_randomiser.path_variables {
+        vars.insert(name, builtin_vars.expand(value));
+    }
+    vars
+}
+
+/// Resolved path variables, for display and for writing portable paths
+#[tauri::command]
+pub fn get_path_variables(app: tauri::AppHandle) -> Result<HashMap<String, String>, String> {
+    let settings = get_app_settings(app.clone())?;
+    Ok(resolve_path_variables(&app, &settings).values().clone())
+}
+
 fn find_path_weight(path: &str, weights: &HashMap<String, f64>) -> f64 {
     weights.get(path).copied().unwrap_or_else(|| {
         weights
@@ -600,31 +955,104 @@ pub fn set_preset_path_weights(
     app_data.lock().unwrap().preset_path_weights = weights;
 }
 
-#[tauri::command]
-pub fn pick_random_file(
-    app: tauri::AppHandle,
-    app_data: State<'_, Mutex<AppStateData>>,
-) -> Option<FileEntry> {
-    let settings = get_app_settings(app.clone()).ok()?;
-    let randomness_level = settings.file_randomiser.randomness_level;
+/// Per-candidate factor breakdown from one scoring pass, in candidate order.
+/// `weights` already has the hard anti-repeat block applied.
+pub(crate) struct CandidateScores {
+    pub weights: Vec<f64>,
+    pub order_scores: Vec<f64>,
+    pub memory_factors: Vec<f64>,
+    pub bookmark_factors: Vec<f64>,
+    /// Global and preset path weights combined; 1.0 when path weights are off
+    pub path_factors: Vec<f64>,
+    pub coverage_factors: Vec<f64>,
+    /// Pick counts after half-life decay; the raw counts when decay is off
+    pub decayed_pick_counts: Vec<f64>,
+    /// 1.0 unless the new arrivals boost is on and the file arrived recently
+    pub new_arrival_factors: Vec<f64>,
+    pub color_streak_factors: Vec<f64>,
+    pub folder_streak_factors: Vec<f64>,
+    /// Recent picks zeroed by the hard anti-repeat block
+    pub hard_blocked: Vec<bool>,
+    pub recency_window: usize,
+}
 
-    let r = (randomness_level as f64 / 100.0).clamp(0.0, 1.0);
-    let mut rng = rand::rng();
+fn age_days(now: DateTime<Utc>, at: DateTime<Utc>) -> f64 {
+    (now - at).num_seconds().max(0) as f64 / 86_400.0
+}
 
-    let mut data = app_data.lock().unwrap();
+/// `0.5^(age / half-life)`; 1 when the half-life is 0 (no decay)
+fn decay_weight(age_days: f64, half_life_days: u32) -> f64 {
+    if half_life_days == 0 {
+        1.0
+    } else {
+        0.5_f64.powf(age_days / half_life_days as f64)
+    }
+}
 
-    let candidates: Vec<(usize, FileEntry)> = data
-        .files
-        .iter()
-        .enumerate()
-        .filter(|(_, f)| !f.excluded)
-        .map(|(i, f)| (i, f.clone()))
-        .collect();
+/// Each candidate's pick count with every pick weighted by
+/// `0.5^(age / half-life)`, so that old history stops dominating the coverage
+/// factor. A file's most recent picks are matched to history entries by path
+/// and decay from their `opened_at`. Picks older than the history decay as if
+/// made at its oldest point: the retention cutoff, or the oldest entry when
+/// that is further back.
+fn decayed_pick_counts(
+    candidates: &[(usize, &FileEntry)],
+    data: &AppStateData,
+    settings: &AppSettings,
+) -> Vec<f64> {
+    let raw = |f: &FileEntry| data.pick_counts.get(&f.id).copied().unwrap_or(0) as f64;
+    let half_life_days = settings.file_randomiser.pick_count_half_life_days;
+    if half_life_days == 0 {
+        return candidates.iter().map(|(_, f)| raw(f)).collect();
+    }
 
-    if candidates.is_empty() {
-        return None;
+    let now = Utc::now();
+    let decay = |opened_at| decay_weight(age_days(now, opened_at), half_life_days);
+
+    // Newest first, so each file's count is matched to its latest picks
+    let mut recent: HashMap<String, Vec<f64>> = HashMap::new();
+    for entry in data.history.iter().rev() {
+        recent
+            .entry(path_key(&entry.path))
+            .or_default()
+            .push(decay(entry.opened_at));
     }
 
+    let retention_days = settings.file_randomiser.history_retention_days as f64;
+    let oldest_days = data
+        .history
+        .first()
+        .map(|h| age_days(now, h.opened_at))
+        .unwrap_or(0.0);
+    let untracked = decay_weight(retention_days.max(oldest_days), half_life_days);
+
+    candidates
+        .iter()
+        .map(|(_, f)| {
+            let count = raw(f) as usize;
+            let tracked = recent
+                .get(&path_key(&f.path))
+                .map(Vec::as_slice)
+                .unwrap_or(&[]);
+            let matched = count.min(tracked.len());
+            tracked[..matched].iter().sum::<f64>() + (count - matched) as f64 * untracked
+        })
+        .collect()
+}
+
+/// Weight every candidate exactly as the picker samples them. Candidates are
+/// `(index, file)` pairs where the index is the file's position in its source
+/// list. `preset_path_weights` is the preset layer of the path weights: the
+/// loaded preset for normal picks, or a blend component for blended picks.
+pub(crate) fn score_candidates(
+    candidates: &[(usize, &FileEntry)],
+    data: &AppStateData,
+    settings: &AppSettings,
+    preset_path_weights: &HashMap<String, f64>,
+) -> CandidateScores {
+    let randomness_level = settings.file_randomiser.randomness_level;
+    let r = (randomness_level as f64 / 100.0).clamp(0.0, 1.0);
+
     let len = candidates.len();
     let last_index = candidates
         .iter()
@@ -633,8 +1061,9 @@ pub fn pick_random_file(
 
     // --- How large a recency window to penalise ---
     // Scales with library size but caps so it doesn't dominate huge libraries.
-    // e.g. 50 files → window ~10, 1000 files → window ~50, 10000 → window ~100
-    let recency_window = ((len as f64).sqrt() * 4.0).clamp(15.0, 200.0) as usize;
+    // With the balanced profile: 50 files → window 28, 1000 → 126, 10000 → 200
+    let tuning = &settings.file_randomiser.tuning;
+    let recency_window = tuning.recency_window(len);
 
     // --- ORDER BIAS CURVE ---
     // Drops very steeply so mid-randomness doesn't visibly favour the next-in-order file.
@@ -668,9 +1097,7 @@ pub fn pick_random_file(
                     .and_then(|b| b.color.clone())
                     .map(|c| c.to_uppercase());
                 let folder = match &f.path {
-                    FilePath::Path(p) => p
-                        .parent()
-                        .map(|pp| pp.to_string_lossy().to_string()),
+                    FilePath::Path(p) => p.parent().map(|pp| pp.to_string_lossy().to_string()),
                     FilePath::Url(_) => None,
                 };
                 (color, folder)
@@ -680,23 +1107,49 @@ pub fn pick_random_file(
         Vec::new()
     };
 
-    let total_picks: u32 = candidates
-        .iter()
-        .map(|(_, f)| data.pick_counts.get(&f.id).copied().unwrap_or(0))
-        .sum();
-    let avg_picks = total_picks as f64 / candidates.len() as f64;
+    let decayed_pick_counts = decayed_pick_counts(candidates, data, settings);
+    let avg_picks = decayed_pick_counts.iter().sum::<f64>() / candidates.len() as f64;
+
+    // --- NEW ARRIVALS ---
+    // A file that arrived after most of the history was made would otherwise
+    // get a big coverage boost for picks it was never around for. With the
+    // boost on, its coverage is measured only against the picks made since
+    // it arrived, and the decaying boost decides how strongly it surfaces.
+    let arrivals = &settings.file_randomiser.new_arrivals;
+    let arrivals_enabled = arrivals.enabled && arrivals.half_life_days > 0;
+    let now = Utc::now();
+    // picks_since[i] = (decayed) picks from history[i] onwards
+    let picks_since: Vec<f64> = if arrivals_enabled {
+        let half_life_days = settings.file_randomiser.pick_count_half_life_days;
+        let mut sums = vec![0.0; data.history.len() + 1];
+        for (i, entry) in data.history.iter().enumerate().rev() {
+            sums[i] = sums[i + 1] + decay_weight(age_days(now, entry.opened_at), half_life_days);
+        }
+        sums
+    } else {
+        Vec::new()
+    };
+
+    // Weight keys may use ${VAR} roots; resolve once per scoring pass
+    let global_path_weights = data
+        .path_variables
+        .expand_keys(&settings.file_randomiser.path_weights);
+    let preset_path_weights = data.path_variables.expand_keys(preset_path_weights);
 
-    // Build weights and capture per-candidate factor breakdown so we can attach
-    // a diagnostics row to the resulting HistoryEntry (for tuning/export later).
     let mut order_scores = Vec::with_capacity(len);
     let mut memory_factors = Vec::with_capacity(len);
+    let mut bookmark_factors = Vec::with_capacity(len);
+    let mut path_factors = Vec::with_capacity(len);
+    let mut coverage_factors = Vec::with_capacity(len);
+    let mut new_arrival_factors = Vec::with_capacity(len);
     let mut color_streak_factors = Vec::with_capacity(len);
     let mut folder_streak_factors = Vec::with_capacity(len);
-    let weights: Vec<f64> = candidates
+    let mut weights: Vec<f64> = candidates
         .iter()
-        .map(|(idx, file)| {
+        .zip(&decayed_pick_counts)
+        .map(|((idx, file), &file_picks)| {
             let fwd_dist = ((*idx + len - last_index) % len) as f64;
-            let sigma = (len as f64 * 0.03).max(1.5);
+            let sigma = (len as f64 * tuning.order_sigma).max(1.5);
             let order_w = (-((fwd_dist - 1.0).powi(2)) / (2.0 * sigma * sigma)).exp();
 
             let recency_penalty = if Some(file.id) == data.last_picked_id {
@@ -713,20 +1166,34 @@ pub fn pick_random_file(
             };
 
             let memory_factor = 1.0 - memory_influence * (1.0 - recency_penalty);
-            let adjusted_bookmark = compute_bookmark_factor(file, &settings);
+            let adjusted_bookmark = compute_bookmark_factor(file, settings);
             let path_weight = if settings.file_randomiser.path_weights_enabled {
                 let path_str = match &file.path {
                     FilePath::Path(p) => p.to_string_lossy().to_string(),
                     FilePath::Url(u) => u.to_string(),
                 };
-                find_path_weight(&path_str, &settings.file_randomiser.path_weights)
-                    * find_path_weight(&path_str, &data.preset_path_weights)
+                find_path_weight(&path_str, &global_path_weights)
+                    * find_path_weight(&path_str, &preset_path_weights)
             } else {
                 1.0
             };
 
-            let file_picks = data.pick_counts.get(&file.id).copied().unwrap_or(0) as f64;
-            let coverage_factor = ((avg_picks + 1.0) / (file_picks + 1.0)).sqrt();
+            let (expected_picks, new_arrival_factor) = match file.first_seen {
+                Some(first_seen) if arrivals_enabled => {
+                    let arrived_at = match file.modified {
+                        Some(modified) if arrivals.use_modified => first_seen.max(modified),
+                        _ => first_seen,
+                    };
+                    let fade = decay_weight(age_days(now, arrived_at), arrivals.half_life_days);
+                    let since = data.history.partition_point(|h| h.opened_at < first_seen);
+                    (
+                        avg_picks.min(picks_since[since] / len as f64),
+                        1.0 + (arrivals.boost.max(1.0) - 1.0) * fade,
+                    )
+                }
+                _ => (avg_picks, 1.0),
+            };
+            let coverage_factor = ((expected_picks + 1.0) / (file_picks + 1.0)).sqrt();
 
             // Streak suppression. Bookmark colour always applies; folder is
             // softened proportional to user-set path weight so a 5x folder
@@ -738,9 +1205,7 @@ pub fn pick_random_file(
                     .and_then(|b| b.color.as_ref())
                     .map(|c| c.to_uppercase());
                 let file_folder = match &file.path {
-                    FilePath::Path(p) => p
-                        .parent()
-                        .map(|pp| pp.to_string_lossy().to_string()),
+                    FilePath::Path(p) => p.parent().map(|pp| pp.to_string_lossy().to_string()),
                     FilePath::Url(_) => None,
                 };
 
@@ -748,7 +1213,7 @@ pub fn pick_random_file(
                 if let Some(fc) = &file_color {
                     if let Some((prev_c, _)) = recent_streak_meta.first() {
                         if prev_c.as_deref() == Some(fc.as_str()) {
-                            color_f = 0.4;
+                            color_f = tuning.color_streak_last;
                         }
                     }
                     if color_f >= 1.0
@@ -756,7 +1221,7 @@ pub fn pick_random_file(
                             .iter()
                             .any(|(c, _)| c.as_deref() == Some(fc.as_str()))
                     {
-                        color_f = 0.7;
+                        color_f = tuning.color_streak_recent;
                     }
                 }
 
@@ -764,7 +1229,7 @@ pub fn pick_random_file(
                 if let Some(ff) = &file_folder {
                     if let Some((_, prev_f)) = recent_streak_meta.first() {
                         if prev_f.as_deref() == Some(ff.as_str()) {
-                            raw_folder_f = 0.5;
+                            raw_folder_f = tuning.folder_streak_last;
                         }
                     }
                     if raw_folder_f >= 1.0
@@ -772,7 +1237,7 @@ pub fn pick_random_file(
                             .iter()
                             .any(|(_, f)| f.as_deref() == Some(ff.as_str()))
                     {
-                        raw_folder_f = 0.8;
+                        raw_folder_f = tuning.folder_streak_recent;
                     }
                 }
                 let folder_f = if path_weight > 1.0 {
@@ -787,9 +1252,13 @@ pub fn pick_random_file(
                 (1.0, 1.0)
             };
 
-            let base = 1.0 + order_w * order_influence * 10.0;
+            let base = 1.0 + order_w * order_influence * tuning.order_boost;
             order_scores.push(order_w);
             memory_factors.push(memory_factor);
+            bookmark_factors.push(adjusted_bookmark);
+            path_factors.push(path_weight);
+            coverage_factors.push(coverage_factor);
+            new_arrival_factors.push(new_arrival_factor);
             color_streak_factors.push(color_streak);
             folder_streak_factors.push(folder_streak);
             (base
@@ -797,6 +1266,7 @@ pub fn pick_random_file(
                 * adjusted_bookmark
                 * path_weight
                 * coverage_factor
+                * new_arrival_factor
                 * color_streak
                 * folder_streak)
                 .max(1e-9)
@@ -805,8 +1275,7 @@ pub fn pick_random_file(
 
     // --- Hard anti-repeat: never pick any of the last N picks when alternatives exist ---
     // N scales with recency window. For ~450 files this blocks ~28 recent picks.
-    let mut weights = weights;
-    let hard_block_n = ((recency_window / 5).max(3)).min(20);
+    let hard_block_n = ((recency_window / 5).max(3)).min(tuning.hard_block_max as usize);
     let blocked: std::collections::HashSet<u64> = data
         .last_picked_id
         .iter()
@@ -814,92 +1283,198 @@ pub fn pick_random_file(
         .chain(data.recency_list.iter().rev().take(hard_block_n).copied())
         .collect();
     let blockable = len.saturating_sub(blocked.len());
+    let mut hard_blocked = vec![false; len];
     if blockable >= 1 {
         for (i, (_, f)) in candidates.iter().enumerate() {
             if blocked.contains(&f.id) {
                 weights[i] = 0.0;
+                hard_blocked[i] = true;
             }
         }
     }
 
-    let dist = WeightedIndex::new(&weights).ok()?;
-    let chosen = dist.sample(&mut rng);
-    let (_, file) = candidates[chosen].clone();
+    CandidateScores {
+        weights,
+        order_scores,
+        memory_factors,
+        bookmark_factors,
+        path_factors,
+        coverage_factors,
+        decayed_pick_counts,
+        new_arrival_factors,
+        color_streak_factors,
+        folder_streak_factors,
+        hard_blocked,
+        recency_window,
+    }
+}
 
-    // --- Build diagnostics for this pick ---
-    let diagnostics = {
-        let mean = |v: &[f64]| {
-            if v.is_empty() {
-                0.0
-            } else {
-                v.iter().sum::<f64>() / v.len() as f64
-            }
-        };
-        let mut sorted = weights.clone();
-        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-        let median = if sorted.is_empty() {
+/// Summarise one scoring pass and the chosen candidate for the history entry.
+pub(crate) fn build_pick_diagnostics(
+    candidates: &[(usize, &FileEntry)],
+    scores: &CandidateScores,
+    chosen: usize,
+    data: &AppStateData,
+    settings: &AppSettings,
+) -> PickDiagnostics {
+    let weights = &scores.weights;
+    let file = candidates[chosen].1;
+    let recency_window = scores.recency_window;
+
+    let mean = |v: &[f64]| {
+        if v.is_empty() {
             0.0
-        } else if sorted.len() % 2 == 0 {
-            (sorted[sorted.len() / 2 - 1] + sorted[sorted.len() / 2]) / 2.0
         } else {
-            sorted[sorted.len() / 2]
-        };
-        let weight_min = weights.iter().cloned().fold(f64::INFINITY, f64::min);
-        let weight_max = weights.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+            v.iter().sum::<f64>() / v.len() as f64
+        }
+    };
+    let mut sorted = weights.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let median = if sorted.is_empty() {
+        0.0
+    } else if sorted.len().is_multiple_of(2) {
+        (sorted[sorted.len() / 2 - 1] + sorted[sorted.len() / 2]) / 2.0
+    } else {
+        sorted[sorted.len() / 2]
+    };
+    let weight_min = weights.iter().cloned().fold(f64::INFINITY, f64::min);
+    let weight_max = weights.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
 
-        let bookmarked_weights: Vec<f64> = candidates
-            .iter()
-            .zip(weights.iter())
-            .filter(|((_, f), _)| f.bookmark.as_ref().and_then(|b| b.color.as_ref()).is_some())
-            .map(|(_, w)| *w)
-            .collect();
-        let unbookmarked_weights: Vec<f64> = candidates
-            .iter()
-            .zip(weights.iter())
-            .filter(|((_, f), _)| f.bookmark.as_ref().and_then(|b| b.color.as_ref()).is_none())
-            .map(|(_, w)| *w)
-            .collect();
+    let bookmarked_weights: Vec<f64> = candidates
+        .iter()
+        .zip(weights.iter())
+        .filter(|((_, f), _)| f.bookmark.as_ref().and_then(|b| b.color.as_ref()).is_some())
+        .map(|(_, w)| *w)
+        .collect();
+    let unbookmarked_weights: Vec<f64> = candidates
+        .iter()
+        .zip(weights.iter())
+        .filter(|((_, f), _)| f.bookmark.as_ref().and_then(|b| b.color.as_ref()).is_none())
+        .map(|(_, w)| *w)
+        .collect();
 
-        let recency_penalised = candidates
-            .iter()
-            .filter(|(_, f)| {
-                Some(f.id) == data.last_picked_id
-                    || data
-                        .recency_list
-                        .iter()
-                        .rev()
-                        .position(|&id| id == f.id)
-                        .map(|pos| pos < recency_window)
-                        .unwrap_or(false)
-            })
-            .count();
-
-        crate::models::PickDiagnostics {
-            randomness_level,
-            candidates: len as u32,
-            bookmark_pref_enabled: settings.file_randomiser.bookmark_preference.enabled,
-            recency_window: recency_window as u32,
-            recency_penalised: recency_penalised as u32,
-            weight_min,
-            weight_max,
-            weight_mean: mean(&weights),
-            weight_median: median,
-            bookmarked_count: bookmarked_weights.len() as u32,
-            bookmarked_mean: mean(&bookmarked_weights),
-            unbookmarked_count: unbookmarked_weights.len() as u32,
-            unbookmarked_mean: mean(&unbookmarked_weights),
-            chosen_weight: weights[chosen],
-            chosen_order_score: order_scores[chosen],
-            chosen_memory_factor: memory_factors[chosen],
-            chosen_color_streak_factor: color_streak_factors[chosen],
-            chosen_folder_streak_factor: folder_streak_factors[chosen],
-            chosen_bookmark_color: file.bookmark.as_ref().and_then(|b| b.color.clone()),
-            chosen_bookmark_global: file.bookmark.as_ref().map(|b| b.is_global).unwrap_or(false),
+    let recency_penalised = candidates
+        .iter()
+        .filter(|(_, f)| {
+            Some(f.id) == data.last_picked_id
+                || data
+                    .recency_list
+                    .iter()
+                    .rev()
+                    .position(|&id| id == f.id)
+                    .map(|pos| pos < recency_window)
+                    .unwrap_or(false)
+        })
+        .count();
+
+    PickDiagnostics {
+        randomness_level: settings.file_randomiser.randomness_level,
+        candidates: candidates.len() as u32,
+        bookmark_pref_enabled: settings.file_randomiser.bookmark_preference.enabled,
+        recency_window: recency_window as u32,
+        recency_penalised: recency_penalised as u32,
+        weight_min,
+        weight_max,
+        weight_mean: mean(weights),
+        weight_median: median,
+        bookmarked_count: bookmarked_weights.len() as u32,
+        bookmarked_mean: mean(&bookmarked_weights),
+        unbookmarked_count: unbookmarked_weights.len() as u32,
+        unbookmarked_mean: mean(&unbookmarked_weights),
+        chosen_weight: weights[chosen],
+        chosen_order_score: scores.order_scores[chosen],
+        chosen_memory_factor: scores.memory_factors[chosen],
+        chosen_color_streak_factor: scores.color_streak_factors[chosen],
+        chosen_folder_streak_factor: scores.folder_streak_factors[chosen],
+        chosen_bookmark_color: file.bookmark.as_ref().and_then(|b| b.color.clone()),
+        chosen_bookmark_global: file.bookmark.as_ref().map(|b| b.is_global).unwrap_or(false),
+        blend_id: None,
+        blend_preset_id: None,
+        blend_preset_probability: None,
+        tuning_profile: Some(settings.file_randomiser.tuning.profile.clone()),
+        series_folder: None,
+        constraints_applied: 0,
+        constraint_violations: 0,
+        skipped_missing: 0,
+    }
+}
+
+/// Whether a local file is still on disk. URLs are assumed to exist.
+fn file_exists(file: &FileEntry) -> bool {
+    match &file.path {
+        FilePath::Path(p) => p.exists(),
+        FilePath::Url(_) => true,
+    }
+}
+
+/// Run `choose` until it picks a candidate that still exists on disk. It is
+/// passed which candidates have been found missing so far and must not pick
+/// those again. Returns the choice, if any, and the indices of the missing
+/// candidates.
+pub(crate) fn choose_existing<T>(
+    candidates: &[(usize, &FileEntry)],
+    mut choose: impl FnMut(&[bool]) -> Option<(usize, T)>,
+) -> (Option<(usize, T)>, Vec<usize>) {
+    let mut is_missing = vec![false; candidates.len()];
+    let mut missing = vec![];
+    loop {
+        let Some((chosen, extra)) = choose(&is_missing) else {
+            return (None, missing);
+        };
+        if is_missing[chosen] {
+            return (None, missing);
         }
-    };
+        if file_exists(candidates[chosen].1) {
+            return (Some((chosen, extra)), missing);
+        }
+        is_missing[chosen] = true;
+        missing.push(chosen);
+    }
+}
+
+/// Weighted sample that leaves out missing candidates
+pub(crate) fn sample_weights(
+    weights: &mut [f64],
+    is_missing: &[bool],
+    rng: &mut impl Rng,
+) -> Option<(usize, ())> {
+    for (w, missing) in weights.iter_mut().zip(is_missing) {
+        if *missing {
+            *w = 0.0;
+        }
+    }
+    let dist = WeightedIndex::new(&*weights).ok()?;
+    Some((dist.sample(rng), ()))
+}
 
-    // --- Update state ---
-    data.last_picked_id = Some(file.id);
+/// Drop files found missing at pick time and tell the frontend which paths
+/// went. Their path-keyed pick counts are kept for when a crawl finds them
+/// again.
+pub(crate) fn prune_missing_files(app: &tauri::AppHandle, data: &mut AppStateData, ids: &[u64]) {
+    if ids.is_empty() {
+        return;
+    }
+    let paths: Vec<String> = data
+        .files
+        .iter()
+        .filter(|f| ids.contains(&f.id))
+        .map(|f| path_key(&f.path))
+        .collect();
+    data.files.retain(|f| !ids.contains(&f.id));
+    let _ = app.emit("files-missing", paths);
+}
+
+/// Record a pick in the randomiser state, then open the file and log it to
+/// history with its diagnostics.
+pub(crate) fn commit_pick(
+    app: tauri::AppHandle,
+    mut data: MutexGuard<'_, AppStateData>,
+    settings: &AppSettings,
+    file: &FileEntry,
+    recency_window: usize,
+    diagnostics: PickDiagnostics,
+) {
+    let previous_last_picked_id = data.last_picked_id.replace(file.id);
     *data.pick_counts.entry(file.id).or_insert(0) += 1;
 
     // Update recency list — push to back, trim front if over 2x window
@@ -907,23 +1482,184 @@ pub fn pick_random_file(
     data.recency_list.push(file.id);
 
     let max_recency_len = recency_window + 10;
+    let mut recency_trimmed = vec![];
     if data.recency_list.len() > max_recency_len {
         let drain_count = data.recency_list.len() - max_recency_len;
-        data.recency_list.drain(0..drain_count);
+        recency_trimmed = data.recency_list.drain(0..drain_count).collect();
     }
 
+    let opened_at = Utc::now();
+    let session = record_pick(&app, settings, diagnostics.blend_id.clone(), opened_at);
+    let path = path_key(&file.path);
+    record_history(
+        &app,
+        &mut data,
+        HistoryEntry {
+            id: file.id,
+            name: file.name.clone(),
+            path: FilePath::Path(path.clone().into()),
+            opened_at,
+            diagnostics: Some(diagnostics),
+            session_id: Some(session.session_id.clone()),
+        },
+    );
+
+    {
+        let undo_state = app.state::<PickUndoStack>();
+        let mut undo = undo_state.0.lock().unwrap();
+        undo.push(PickUndo {
+            file_id: file.id,
+            opened_at,
+            previous_last_picked_id,
+            recency_trimmed,
+            session,
+        });
+        if undo.len() > MAX_PICK_UNDO {
+            undo.remove(0);
+        }
+    }
     drop(data);
 
     let _ = app.emit("file-picked", ());
-    let _ = open_file_tracked(
+    let _ = open_file_tracked(app, path, None, None);
+}
+
+/// Revert the most recent pick still on the undo stack: its history entry,
+/// pick count, recency entry, last-picked file and session bookkeeping.
+/// Only picks in the open session can be undone; returns the removed entry,
+/// or None when there is nothing left to undo.
+#[tauri::command]
+pub fn undo_last_pick(
+    app: tauri::AppHandle,
+    app_data: State<'_, Mutex<AppStateData>>,
+    undo_stack: State<'_, PickUndoStack>,
+) -> Result<Option<HistoryEntry>, String> {
+    let settings = get_app_settings(app.clone())?;
+    let mut data = app_data.lock().unwrap();
+    let mut stack = undo_stack.0.lock().unwrap();
+
+    let Some(undo) = stack.pop() else {
+        return Ok(None);
+    };
+    if !is_open_session(&app, &undo.session.session_id) {
+        // Everything older belongs to an earlier session too
+        stack.clear();
+        return Ok(None);
+    }
+    let Some(index) = data
+        .history
+        .iter()
+        .rposition(|h| h.id == undo.file_id && h.opened_at == undo.opened_at)
+    else {
+        stack.clear();
+        return Ok(None);
+    };
+    drop(stack);
+
+    let entry = data.history.remove(index);
+    rewrite_history(&app, &data.history)?;
+
+    if let Some(count) = data.pick_counts.get_mut(&undo.file_id) {
+        *count = count.saturating_sub(1);
+        if *count == 0 {
+            data.pick_counts.remove(&undo.file_id);
+        }
+    }
+    if let Some(pos) = data.recency_list.iter().rposition(|id| *id == undo.file_id) {
+        data.recency_list.remove(pos);
+    }
+    data.recency_list.splice(0..0, undo.recency_trimmed);
+    data.last_picked_id = undo.previous_last_picked_id;
+    schedule_stats_save(&app);
+
+    undo_pick(&app, &settings, undo.session);
+    drop(data);
+
+    let _ = app.emit("pick-undone", ());
+    Ok(Some(entry))
+}
+
+#[tauri::command]
+pub fn pick_random_file(
+    app: tauri::AppHandle,
+    app_data: State<'_, Mutex<AppStateData>>,
+    sessions: State<'_, PickSessions>,
+) -> Option<FileEntry> {
+    let settings = get_app_settings(app.clone()).ok()?;
+    let mut rng = rand::rng();
+
+    let mut data = app_data.lock().unwrap();
+
+    let candidates: Vec<(usize, &FileEntry)> = data
+        .files
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| !f.excluded)
+        .collect();
+
+    if candidates.is_empty() {
+        return None;
+    }
+
+    let mut scores = score_candidates(&candidates, &data, &settings, &data.preset_path_weights);
+
+    let mut constraints = None;
+    let (picked, missing) = match settings.file_randomiser.pick_mode {
+        PickMode::Weighted => {
+            let session_id = sessions
+                .0
+                .lock()
+                .unwrap()
+                .continuing(&settings, None, Utc::now())
+                .map(|s| s.id.clone());
+            let check = enforce_constraints(
+                &candidates,
+                &mut scores.weights,
+                &data,
+                &settings.file_randomiser.pick_constraints,
+                session_id.as_deref(),
+            );
+            let (picked, missing) = choose_existing(&candidates, |is_missing| {
+                sample_weights(&mut scores.weights, is_missing, &mut rng)
+            });
+            if let Some((chosen, _)) = picked {
+                constraints = Some((check.applied, check.violations(candidates[chosen].1)));
+            }
+            (picked.map(|(chosen, _)| (chosen, None)), missing)
+        }
+        PickMode::Series => choose_existing(&candidates, |is_missing| {
+            // Series mode only sees the files not found missing
+            let available: Vec<usize> = (0..candidates.len()).filter(|i| !is_missing[*i]).collect();
+            let subset: Vec<(usize, &FileEntry)> =
+                available.iter().map(|i| candidates[*i]).collect();
+            let (chosen, folder) =
+                choose_series_file(&subset, &data, &settings.file_randomiser.series, &mut rng)?;
+            Some((available[chosen], Some(folder)))
+        }),
+    };
+    let missing_ids: Vec<u64> = missing.iter().map(|i| candidates[*i].1.id).collect();
+    let Some((chosen, series_folder)) = picked else {
+        prune_missing_files(&app, &mut data, &missing_ids);
+        return None;
+    };
+
+    let file = candidates[chosen].1.clone();
+    let mut diagnostics = build_pick_diagnostics(&candidates, &scores, chosen, &data, &settings);
+    diagnostics.series_folder = series_folder;
+    diagnostics.skipped_missing = missing_ids.len() as u32;
+    if let Some((applied, violations)) = constraints {
+        diagnostics.constraints_applied = applied;
+        diagnostics.constraint_violations = violations;
+    }
+    prune_missing_files(&app, &mut data, &missing_ids);
+
+    commit_pick(
         app,
-        match &file.path {
-            FilePath::Path(p) => p.to_string_lossy().to_string(),
-            FilePath::Url(u) => u.to_string(),
-        },
-        Some(file.id),
-        Some(file.name.clone()),
-        Some(diagnostics),
+        data,
+        &settings,
+        &file,
+        scores.recency_window,
+        diagnostics,
     );
 
     Some(file)
@@ -948,7 +1684,6 @@ pub fn open_file_by_id(
         },
         Some(file.id),
         Some(file.name.clone()),
-        None,
     );
 
     Some(file)
@@ -957,81 +1692,10 @@ pub fn open_file_by_id(
 #[tauri::command]
 pub fn update_app_state(app_data: State<'_, Mutex<AppStateData>>, new_data: AppStateData) {
     let mut data = app_data.lock().unwrap();
+    // Resolved variables are runtime-only; keep them until the next crawl
+    let path_variables = std::mem::take(&mut data.path_variables);
     *data = new_data;
-}
-
-#[tauri::command]
-pub fn open_presets_folder(app: tauri::AppHandle) -> Result<(), String> {
-    let presets_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| e.to_string())?
-        .join("presets");
-
-    std::fs::create_dir_all(&presets_dir).map_err(|e| e.to_string())?;
-
-    app.opener()
-        .open_path(presets_dir.to_string_lossy(), None::<String>)
-        .map_err(|e| e.to_string())?;
-
-    Ok(())
-}
-
-#[tauri::command]
-pub fn get_presets(app: tauri::AppHandle) -> Result<Vec<RandomiserPreset>, String> {
-    let presets_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| e.to_string())?
-        .join("presets");
-
-    // Make sure the folder exists
-    std::fs::create_dir_all(&presets_dir).map_err(|e| e.to_string())?;
-
-    let mut presets = Vec::new();
-
-    for entry in std::fs::read_dir(&presets_dir).map_err(|e| e.to_string())? {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
-
-        // Only process .json files
-        if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            let file_content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
-
-            // Try to deserialize; skip invalid files
-            if let Ok(preset) = serde_json::from_str::<RandomiserPreset>(&file_content) {
-                presets.push(preset);
-            } else {
-                eprintln!("Skipping invalid preset file: {:?}", path);
-            }
-        }
-    }
-
-    Ok(presets)
-}
-
-#[tauri::command]
-pub fn save_preset(app: tauri::AppHandle, preset: RandomiserPreset) -> Result<(), String> {
-    let presets_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| e.to_string())?
-        .join("presets");
-
-    // Ensure the presets folder exists
-    std::fs::create_dir_all(&presets_dir).map_err(|e| e.to_string())?;
-
-    // Sanitize name for file system (basic)
-    let safe_name = preset
-        .name
-        .replace(|c: char| !c.is_alphanumeric() && c != '-' && c != '_', "_");
-
-    let file_path = presets_dir.join(format!("{}.json", safe_name));
-    let json_data = serde_json::to_string_pretty(&preset).map_err(|e| e.to_string())?;
-
-    std::fs::write(file_path, json_data).map_err(|e| e.to_string())?;
-
-    Ok(())
+    data.path_variables = path_variables;
 }
 
 #[tauri::command]
@@ -1056,8 +1720,6 @@ pub fn get_file_scores(
     app_data: State<'_, Mutex<AppStateData>>,
 ) -> Result<Vec<FileScore>, String> {
     let settings = get_app_settings(app.clone())?;
-    let randomness_level = settings.file_randomiser.randomness_level;
-    let r = (randomness_level as f64 / 100.0).clamp(0.0, 1.0);
 
     let data = app_data.lock().unwrap();
 
@@ -1072,181 +1734,99 @@ pub fn get_file_scores(
         return Ok(vec![]);
     }
 
-    let len = candidates.len();
-    let last_index = candidates
-        .iter()
-        .position(|(_, f)| Some(f.id) == data.last_picked_id)
-        .unwrap_or(0);
-    let recency_window = ((len as f64).sqrt() * 4.0).clamp(15.0, 200.0) as usize;
-    let order_influence = (1.0 - r).powf(6.0);
-    let memory_influence = (1.0 - (1.0 - r).powi(2)).max(0.5);
-
-    let streaks_enabled = r < 1.0;
-    let recent_streak_meta: Vec<(Option<String>, Option<String>)> = if streaks_enabled {
-        let mut hist: Vec<&crate::models::HistoryEntry> = data.history.iter().collect();
-        hist.sort_by_key(|h| h.opened_at);
-        hist.iter()
-            .rev()
-            .take(3)
-            .filter_map(|h| data.files.iter().find(|f| f.id == h.id))
-            .map(|f| {
-                let color = f
-                    .bookmark
-                    .as_ref()
-                    .and_then(|b| b.color.clone())
-                    .map(|c| c.to_uppercase());
-                let folder = match &f.path {
-                    FilePath::Path(p) => p
-                        .parent()
-                        .map(|pp| pp.to_string_lossy().to_string()),
-                    FilePath::Url(_) => None,
-                };
-                (color, folder)
-            })
-            .collect()
-    } else {
-        Vec::new()
-    };
-
-    let hard_block_n = ((recency_window / 5).max(3)).min(20);
-    let blocked: std::collections::HashSet<u64> = data
-        .last_picked_id
-        .iter()
-        .copied()
-        .chain(data.recency_list.iter().rev().take(hard_block_n).copied())
-        .collect();
-    let any_unblocked = len > blocked.len();
-
-    let total_picks: u32 = candidates
-        .iter()
-        .map(|(_, f)| data.pick_counts.get(&f.id).copied().unwrap_or(0))
-        .sum();
-    let avg_picks = total_picks as f64 / candidates.len() as f64;
+    // Mirror pick_random_file: the last N picks are hard-excluded from sampling
+    // (as long as at least one candidate remains unblocked), so their weight is zero.
+    let scores = score_candidates(&candidates, &data, &settings, &data.preset_path_weights);
 
     let scores: Vec<FileScore> = candidates
         .iter()
-        .map(|(idx, file)| {
-            let fwd_dist = ((*idx + len - last_index) % len) as f64;
-            let sigma = (len as f64 * 0.03).max(1.5);
-            let order_w = (-((fwd_dist - 1.0).powi(2)) / (2.0 * sigma * sigma)).exp();
-
-            let is_last_picked = Some(file.id) == data.last_picked_id;
-            let recency_penalty = if is_last_picked {
-                0.0
-            } else if let Some(pos) = data.recency_list.iter().rev().position(|&id| id == file.id) {
-                if pos < recency_window {
-                    let frac = pos as f64 / recency_window as f64;
-                    frac.powf(2.)
-                } else {
-                    1.0
-                }
-            } else {
-                1.0
-            };
-
-            let memory_factor = 1.0 - memory_influence * (1.0 - recency_penalty);
-            let bookmark_factor = compute_bookmark_factor(file, &settings);
-            let path_weight = if settings.file_randomiser.path_weights_enabled {
-                let path_str = match &file.path {
-                    FilePath::Path(p) => p.to_string_lossy().to_string(),
-                    FilePath::Url(u) => u.to_string(),
-                };
-                find_path_weight(&path_str, &settings.file_randomiser.path_weights)
-                    * find_path_weight(&path_str, &data.preset_path_weights)
-            } else {
-                1.0
-            };
-
-            let file_picks = data.pick_counts.get(&file.id).copied().unwrap_or(0) as f64;
-            let coverage_factor = ((avg_picks + 1.0) / (file_picks + 1.0)).sqrt();
+        .enumerate()
+        .map(|(i, (_, file))| FileScore {
+            id: file.id,
+            name: file.name.clone(),
+            is_excluded: file.excluded,
+            order_score: scores.order_scores[i],
+            memory_factor: scores.memory_factors[i],
+            bookmark_factor: scores.bookmark_factors[i],
+            coverage_factor: scores.coverage_factors[i],
+            pick_count: data.pick_counts.get(&file.id).copied().unwrap_or(0),
+            decayed_pick_count: scores.decayed_pick_counts[i],
+            new_arrival_factor: scores.new_arrival_factors[i],
+            total_weight: scores.weights[i],
+        })
+        .collect();
 
-            let (color_streak, folder_streak) = if streaks_enabled {
-                let file_color = file
-                    .bookmark
-                    .as_ref()
-                    .and_then(|b| b.color.as_ref())
-                    .map(|c| c.to_uppercase());
-                let file_folder = match &file.path {
-                    FilePath::Path(p) => p
-                        .parent()
-                        .map(|pp| pp.to_string_lossy().to_string()),
-                    FilePath::Url(_) => None,
-                };
-                let mut color_f = 1.0_f64;
-                if let Some(fc) = &file_color {
-                    if let Some((prev_c, _)) = recent_streak_meta.first() {
-                        if prev_c.as_deref() == Some(fc.as_str()) {
-                            color_f = 0.4;
-                        }
-                    }
-                    if color_f >= 1.0
-                        && recent_streak_meta
-                            .iter()
-                            .any(|(c, _)| c.as_deref() == Some(fc.as_str()))
-                    {
-                        color_f = 0.7;
-                    }
-                }
-                let mut raw_folder_f = 1.0_f64;
-                if let Some(ff) = &file_folder {
-                    if let Some((_, prev_f)) = recent_streak_meta.first() {
-                        if prev_f.as_deref() == Some(ff.as_str()) {
-                            raw_folder_f = 0.5;
-                        }
-                    }
-                    if raw_folder_f >= 1.0
-                        && recent_streak_meta
-                            .iter()
-                            .any(|(_, f)| f.as_deref() == Some(ff.as_str()))
-                    {
-                        raw_folder_f = 0.8;
-                    }
-                }
-                let folder_f = if path_weight > 1.0 {
-                    let t = ((path_weight - 1.0) / 4.0).clamp(0.0, 1.0);
-                    raw_folder_f + (1.0 - raw_folder_f) * t
-                } else {
-                    raw_folder_f
-                };
-                (color_f, folder_f)
-            } else {
-                (1.0, 1.0)
-            };
+    Ok(scores)
+}
 
-            let base = 1.0 + order_w * order_influence * 10.0;
-            // Mirror pick_random_file: the last N picks are hard-excluded from sampling
-            // (as long as at least one candidate remains unblocked), so show their weight as zero.
-            let total = if any_unblocked && blocked.contains(&file.id) {
-                0.0
-            } else {
-                (base
-                    * memory_factor
-                    * bookmark_factor
-                    * path_weight
-                    * coverage_factor
-                    * color_streak
-                    * folder_streak)
-                    .max(1e-9)
-            };
+/// Break down why a file is as likely, or unlikely, as it is to come up on
+/// the next normal pick: every weighting factor, the hard block, its share of
+/// the total weight, its rank and how many picks it would take on average to
+/// come up if nothing changed.
+#[tauri::command]
+pub fn explain_pick(
+    app: tauri::AppHandle,
+    app_data: State<'_, Mutex<AppStateData>>,
+    id: u64,
+) -> Result<PickExplanation, String> {
+    let settings = get_app_settings(app.clone())?;
+    let data = app_data.lock().unwrap();
 
-            FileScore {
-                id: file.id,
-                name: file.name.clone(),
-                is_excluded: file.excluded,
-                order_score: order_w,
-                memory_factor,
-                bookmark_factor,
-                coverage_factor,
-                total_weight: total,
-            }
-        })
+    let file = data
+        .files
+        .iter()
+        .find(|f| f.id == id)
+        .ok_or("File not found in the current crawl")?;
+    let candidates: Vec<(usize, &FileEntry)> = data
+        .files
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| !f.excluded)
         .collect();
+    let mut explanation = PickExplanation {
+        id,
+        name: file.name.clone(),
+        excluded: file.excluded,
+        candidates: candidates.len(),
+        pick_count: data.pick_counts.get(&id).copied().unwrap_or(0),
+        tuning_profile: settings.file_randomiser.tuning.profile.clone(),
+        factors: None,
+        probability: 0.0,
+        rank: None,
+        expected_picks_until_seen: None,
+    };
+    let Some(i) = candidates.iter().position(|(_, f)| f.id == id) else {
+        return Ok(explanation);
+    };
 
-    Ok(scores)
+    let scores = score_candidates(&candidates, &data, &settings, &data.preset_path_weights);
+    let weight = scores.weights[i];
+    let total: f64 = scores.weights.iter().sum();
+    let probability = if total > 0.0 { weight / total } else { 0.0 };
+
+    explanation.factors = Some(PickFactors {
+        order_score: scores.order_scores[i],
+        memory_factor: scores.memory_factors[i],
+        bookmark_factor: scores.bookmark_factors[i],
+        path_factor: scores.path_factors[i],
+        coverage_factor: scores.coverage_factors[i],
+        decayed_pick_count: scores.decayed_pick_counts[i],
+        new_arrival_factor: scores.new_arrival_factors[i],
+        color_streak_factor: scores.color_streak_factors[i],
+        folder_streak_factor: scores.folder_streak_factors[i],
+        hard_blocked: scores.hard_blocked[i],
+        weight,
+    });
+    explanation.probability = probability;
+    if probability > 0.0 {
+        explanation.rank = Some(1 + scores.weights.iter().filter(|w| **w > weight).count());
+        explanation.expected_picks_until_seen = Some(1.0 / probability);
+    }
+    Ok(explanation)
 }
 
 fn apply_bookmark(
-    files: &mut Vec<crate::models::FileEntry>,
+    files: &mut [crate::models::FileEntry],
     hashes: &std::collections::HashSet<&str>,
     color: &Option<String>,
     is_global: bool,
@@ -409,13 +409,12 @@ pub fn restore_last_sort(
             .read_dir()
             .map(|mut i| i.next().is_none())
             .unwrap_or(false)
+            && std::fs::remove_dir(&dir).is_ok()
         {
-            if std::fs::remove_dir(&dir).is_ok() {
-                emit_log(
-                    &app,
-                    &format!("Removed empty folder {}", dir.to_string_lossy()),
-                );
-            }
+            emit_log(
+                &app,
+                &format!("Removed empty folder {}", dir.to_string_lossy()),
+            );
         }
     }
 
@@ -0,0 +1,813 @@
+use crate::filerandomisercommands::{save_persisted_stats, PathPickCounts, PickUndoStack};
+use crate::historylog::rewrite_history;
+use crate::models::common::remap_path_prefix;
+use crate::models::{
+    AppStateData, ColorCount, DayCount, FolderCount, HistoryAggregates, HistoryEntry,
+    HistoryExportFormat, HistoryExportLayout, HistoryExportOptions, HistoryImportMode,
+    HistoryImportSummary, HistoryPage, HistoryQuery, HistoryRow, PickDiagnostics,
+};
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+use tauri::{Manager, State};
+use tauri_plugin_dialog::{DialogExt, FilePath};
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Columns of docs/data/history.csv
+pub(crate) const HISTORY_COLUMNS: &[&str] = &["pickNumber", "name", "openedAt"];
+
+/// Columns of docs/data/diagnostics.csv. Everything after `name` is a
+/// `PickDiagnostics` field under its serialised name.
+pub(crate) const DIAGNOSTICS_COLUMNS: &[&str] = &[
+    "pickNumber",
+    "openedAt",
+    "name",
+    "randomnessLevel",
+    "candidates",
+    "bookmarkPrefEnabled",
+    "recencyWindow",
+    "recencyPenalised",
+    "weightMin",
+    "weightMax",
+    "weightMean",
+    "weightMedian",
+    "bookmarkedCount",
+    "bookmarkedMean",
+    "unbookmarkedCount",
+    "unbookmarkedMean",
+    "chosenWeight",
+    "chosenOrderScore",
+    "chosenMemoryFactor",
+    "chosenColorStreakFactor",
+    "chosenFolderStreakFactor",
+    "chosenBookmarkColor",
+    "chosenBookmarkGlobal",
+];
+
+/// Columns holding free text rather than numbers or booleans
+const TEXT_COLUMNS: &[&str] = &[
+    "name",
+    "path",
+    "openedAt",
+    "chosenBookmarkColor",
+    "blendId",
+    "blendPresetId",
+    "tuningProfile",
+    "seriesFolder",
+    "sessionId",
+];
+
+/// Selectable columns that neither CSV layout includes by default
+const EXTRA_COLUMNS: &[&str] = &[
+    "id",
+    "path",
+    "blendId",
+    "blendPresetId",
+    "blendPresetProbability",
+    "tuningProfile",
+    "seriesFolder",
+    "constraintsApplied",
+    "constraintViolations",
+    "skippedMissing",
+    "sessionId",
+];
+
+pub(crate) fn path_key(path: &FilePath) -> String {
+    match path {
+        FilePath::Path(p) => p.to_string_lossy().to_string(),
+        FilePath::Url(u) => u.to_string(),
+    }
+}
+
+pub(crate) fn parent_folder(path: &FilePath) -> String {
+    match path {
+        FilePath::Path(p) => p
+            .parent()
+            .map(|pp| pp.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        FilePath::Url(_) => String::new(),
+    }
+}
+
+/// Bookmark colour for a history entry: the colour recorded at pick time, or
+/// the file's current colour when the pick predates diagnostics
+fn entry_color(entry: &HistoryEntry, current_colors: &HashMap<String, String>) -> Option<String> {
+    match &entry.diagnostics {
+        Some(d) => d.chosen_bookmark_color.clone(),
+        None => current_colors.get(&path_key(&entry.path)).cloned(),
+    }
+    .map(|c| c.to_uppercase())
+}
+
+/// History entries matching `query`, in chronological order, paired with their
+/// pick number and colour
+pub(crate) fn filter_history<'a>(
+    data: &'a AppStateData,
+    query: &HistoryQuery,
+) -> Vec<(usize, &'a HistoryEntry, Option<String>)> {
+    let current_colors: HashMap<String, String> = data
+        .files
+        .iter()
+        .filter_map(|f| {
+            let color = f.bookmark.as_ref()?.color.clone()?;
+            Some((path_key(&f.path), color))
+        })
+        .collect();
+
+    // History is kept sorted by opened_at, so the date range is two searches
+    let history = &data.history;
+    let start = query
+        .from
+        .map(|from| history.partition_point(|h| h.opened_at < from))
+        .unwrap_or(0);
+    let end = query
+        .to
+        .map(|to| history.partition_point(|h| h.opened_at < to))
+        .unwrap_or(history.len())
+        .max(start);
+
+    let color = query.color.as_ref().map(|c| c.to_uppercase());
+    let text = query
+        .text
+        .as_ref()
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty());
+    let prefix = query.path_prefix.as_ref().filter(|p| !p.trim().is_empty());
+
+    history[start..end]
+        .iter()
+        .enumerate()
+        .filter_map(|(i, entry)| {
+            let path = path_key(&entry.path);
+            if let Some(prefix) = prefix {
+                remap_path_prefix(&path, prefix, prefix)?;
+            }
+            if let Some(text) = &text {
+                if !entry.name.to_lowercase().contains(text) && !path.to_lowercase().contains(text)
+                {
+                    return None;
+                }
+            }
+            let entry_color = entry_color(entry, &current_colors);
+            if let Some(color) = &color {
+                let matches = match &entry_color {
+                    Some(c) => c == color,
+                    None => color.is_empty(),
+                };
+                if !matches {
+                    return None;
+                }
+            }
+            Some((start + i + 1, entry, entry_color))
+        })
+        .collect()
+}
+
+fn aggregate(rows: &[(usize, &HistoryEntry, Option<String>)]) -> HistoryAggregates {
+    let mut per_day: BTreeMap<String, usize> = BTreeMap::new();
+    let mut per_folder: HashMap<String, usize> = HashMap::new();
+    let mut per_color: HashMap<Option<String>, usize> = HashMap::new();
+
+    for (_, entry, color) in rows {
+        let day = entry
+            .opened_at
+            .with_timezone(&Local)
+            .format("%Y-%m-%d")
+            .to_string();
+        *per_day.entry(day).or_insert(0) += 1;
+        *per_folder.entry(parent_folder(&entry.path)).or_insert(0) += 1;
+        *per_color.entry(color.clone()).or_insert(0) += 1;
+    }
+
+    let mut per_folder: Vec<FolderCount> = per_folder
+        .into_iter()
+        .map(|(folder, count)| FolderCount { folder, count })
+        .collect();
+    per_folder.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.folder.cmp(&b.folder)));
+    let mut per_color: Vec<ColorCount> = per_color
+        .into_iter()
+        .map(|(color, count)| ColorCount { color, count })
+        .collect();
+    per_color.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.color.cmp(&b.color)));
+
+    HistoryAggregates {
+        per_day: per_day
+            .into_iter()
+            .map(|(day, count)| DayCount { day, count })
+            .collect(),
+        per_folder,
+        per_color,
+    }
+}
+
+/// Filtered, paged view of the pick history with per-day, per-folder and
+/// per-colour counts over all matching entries
+#[tauri::command]
+pub fn query_history(app_data: State<'_, Mutex<AppStateData>>, query: HistoryQuery) -> HistoryPage {
+    let data = app_data.lock().unwrap();
+    let mut rows = filter_history(&data, &query);
+    let aggregates = aggregate(&rows);
+    let total = rows.len();
+
+    if !query.oldest_first {
+        rows.reverse();
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    let entries = rows
+        .into_iter()
+        .skip(query.offset)
+        .take(limit)
+        .map(|(pick_number, entry, color)| HistoryRow {
+            pick_number,
+            entry: entry.clone(),
+            color,
+        })
+        .collect();
+
+    HistoryPage {
+        entries,
+        total,
+        offset: query.offset,
+        aggregates,
+    }
+}
+
+/// "File 001" style labels for every name in the history. Names the stats
+/// window labelled keep its label; any others are numbered after them.
+fn anonymised_names(
+    data: &AppStateData,
+    labels: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    let mut unlabelled: Vec<&str> = data
+        .history
+        .iter()
+        .map(|h| h.name.as_str())
+        .filter(|name| !labels.contains_key(*name))
+        .collect();
+    unlabelled.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()).then(a.cmp(b)));
+    unlabelled.dedup();
+
+    let mut names = labels.clone();
+    for (i, name) in unlabelled.into_iter().enumerate() {
+        names.insert(
+            name.to_string(),
+            format!("File {:03}", labels.len() + i + 1),
+        );
+    }
+    names
+}
+
+/// One export row as column name -> value, serialised as a JSON object that
+/// keeps the selected column order
+struct ExportRow(Vec<(String, Value)>);
+
+impl Serialize for ExportRow {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (column, value) in &self.0 {
+            map.serialize_entry(column, value)?;
+        }
+        map.end()
+    }
+}
+
+fn export_row(
+    columns: &[String],
+    pick_number: usize,
+    entry: &HistoryEntry,
+    name: &str,
+) -> Result<ExportRow, String> {
+    let diagnostics = match &entry.diagnostics {
+        Some(d) => serde_json::to_value(d).map_err(|e| e.to_string())?,
+        None => Value::Null,
+    };
+    Ok(ExportRow(
+        columns
+            .iter()
+            .map(|column| {
+                let value = match column.as_str() {
+                    "pickNumber" => Value::from(pick_number),
+                    "id" => Value::from(entry.id),
+                    "name" => Value::from(name),
+                    "path" => Value::from(path_key(&entry.path)),
+                    "sessionId" => Value::from(entry.session_id.clone()),
+                    "openedAt" => {
+                        Value::from(entry.opened_at.to_rfc3339_opts(SecondsFormat::Millis, true))
+                    }
+                    field => diagnostics.get(field).cloned().unwrap_or(Value::Null),
+                };
+                (column.clone(), value)
+            })
+            .collect(),
+    ))
+}
+
+/// Format a number the way JavaScript's `String(n)` does, so CSV output
+/// matches the files the stats window used to build
+fn js_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e21 {
+        format!("{}", n as i64)
+    } else if n != 0.0 && (n.abs() < 1e-6 || n.abs() >= 1e21) {
+        let s = format!("{:e}", n);
+        match s.split_once('e') {
+            Some((mantissa, exp)) if !exp.starts_with('-') => format!("{}e+{}", mantissa, exp),
+            _ => s,
+        }
+    } else {
+        format!("{}", n)
+    }
+}
+
+fn csv_cell(value: &Value) -> String {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Number(n) => match n.as_f64() {
+            Some(f) if n.is_f64() => js_number(f),
+            _ => n.to_string(),
+        },
+        other => other.to_string(),
+    };
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// Render the history as CSV, JSON Lines or a JSON array
+pub(crate) fn render_history_export(
+    data: &AppStateData,
+    options: &HistoryExportOptions,
+) -> Result<String, String> {
+    let layout_columns = match options.layout {
+        HistoryExportLayout::History => HISTORY_COLUMNS,
+        HistoryExportLayout::Diagnostics => DIAGNOSTICS_COLUMNS,
+    };
+    let columns: Vec<String> = match &options.columns {
+        Some(columns) if !columns.is_empty() => columns.clone(),
+        _ => layout_columns.iter().map(|c| c.to_string()).collect(),
+    };
+    if let Some(unknown) = columns.iter().find(|c| {
+        let c = c.as_str();
+        !DIAGNOSTICS_COLUMNS.contains(&c) && !EXTRA_COLUMNS.contains(&c)
+    }) {
+        return Err(format!("Unknown history column: {}", unknown));
+    }
+
+    let query = HistoryQuery {
+        from: options.from,
+        to: options.to,
+        ..Default::default()
+    };
+    let anonymised = options
+        .anonymise
+        .then(|| anonymised_names(data, &options.labels));
+
+    let mut rows = Vec::new();
+    for (pick_number, entry, _) in filter_history(data, &query) {
+        if options.layout == HistoryExportLayout::Diagnostics && entry.diagnostics.is_none() {
+            continue;
+        }
+        let name = anonymised
+            .as_ref()
+            .and_then(|m| m.get(&entry.name))
+            .unwrap_or(&entry.name);
+        rows.push(export_row(&columns, pick_number, entry, name)?);
+    }
+
+    match options.format {
+        HistoryExportFormat::Csv => {
+            let mut lines = vec![columns.join(",")];
+            lines.extend(rows.iter().map(|row| {
+                row.0
+                    .iter()
+                    .map(|(_, value)| csv_cell(value))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            }));
+            Ok(lines.join("\n"))
+        }
+        HistoryExportFormat::JsonLines => rows
+            .into_iter()
+            .map(|row| serde_json::to_string(&row).map_err(|e| e.to_string()))
+            .collect::<Result<Vec<_>, _>>()
+            .map(|lines| lines.join("\n")),
+        HistoryExportFormat::Json => serde_json::to_string_pretty(&rows).map_err(|e| e.to_string()),
+    }
+}
+
+/// Export the pick history or diagnostics through a save dialog. Returns false
+/// if the user cancelled.
+#[tauri::command]
+pub async fn export_history(
+    app: tauri::AppHandle,
+    app_data: State<'_, Mutex<AppStateData>>,
+    options: HistoryExportOptions,
+) -> Result<bool, String> {
+    let content = render_history_export(&app_data.lock().unwrap(), &options)?;
+
+    let stem = match options.layout {
+        HistoryExportLayout::History => "history",
+        HistoryExportLayout::Diagnostics => "diagnostics",
+    };
+    let (filter_name, extension) = match options.format {
+        HistoryExportFormat::Csv => ("CSV", "csv"),
+        HistoryExportFormat::JsonLines => ("JSON Lines", "jsonl"),
+        HistoryExportFormat::Json => ("JSON", "json"),
+    };
+    // Dialog and write on a blocking thread
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = app
+            .dialog()
+            .file()
+            .add_filter(filter_name, &[extension])
+            .set_file_name(format!("{}.{}", stem, extension))
+            .blocking_save_file();
+        match path {
+            Some(FilePath::Path(p)) => std::fs::write(p, content)
+                .map(|_| true)
+                .map_err(|e| e.to_string()),
+            Some(FilePath::Url(_)) => Err("URL paths not supported for history export".to_string()),
+            None => Ok(false), // user cancelled
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Split CSV text into records of fields. Handles quoted fields containing
+/// commas, doubled quotes and line breaks.
+fn parse_csv(content: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_quotes = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("Unterminated quoted field in CSV".into());
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records.retain(|r| !(r.len() == 1 && r[0].trim().is_empty()));
+    Ok(records)
+}
+
+/// Read an export back as rows of column -> value. JSON arrays and JSON
+/// Lines are recognised by their first character; anything else is CSV.
+fn read_import_rows(content: &str) -> Result<Vec<HashMap<String, Value>>, String> {
+    let content = content.trim_start_matches('\u{feff}').trim();
+    if content.starts_with('[') {
+        return serde_json::from_str(content).map_err(|e| format!("Invalid JSON: {}", e));
+    }
+    if content.starts_with('{') {
+        return content
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .enumerate()
+            .map(|(i, l)| {
+                serde_json::from_str(l)
+                    .map_err(|e| format!("Invalid JSON on line {}: {}", i + 1, e))
+            })
+            .collect();
+    }
+
+    let mut records = parse_csv(content)?.into_iter();
+    let header = records.next().ok_or("The file is empty")?;
+    Ok(records
+        .map(|record| {
+            header
+                .iter()
+                .cloned()
+                .zip(record.into_iter().map(Value::from))
+                .collect()
+        })
+        .collect())
+}
+
+/// Turn an exported cell back into a typed JSON value. CSV cells are all
+/// strings; empty cells are null.
+fn import_value(column: &str, value: &Value) -> Value {
+    let Value::String(s) = value else {
+        return value.clone();
+    };
+    if s.is_empty() {
+        return Value::Null;
+    }
+    if TEXT_COLUMNS.contains(&column) {
+        return value.clone();
+    }
+    if let Ok(b) = s.parse::<bool>() {
+        return Value::from(b);
+    }
+    if let Ok(n) = s.parse::<u64>() {
+        return Value::from(n);
+    }
+    if let Ok(n) = s.parse::<i64>() {
+        return Value::from(n);
+    }
+    match s.parse::<f64>() {
+        Ok(n) => Value::from(n),
+        Err(_) => value.clone(),
+    }
+}
+
+fn import_text(row: &HashMap<String, Value>, column: &str) -> Option<String> {
+    match row.get(column)? {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Rebuild a history entry from an export row. Exports without a path column
+/// are matched to crawled files by name, and rows that match no file, or more
+/// than one, are skipped. IDs always come from the current crawl, since exported IDs belong
+/// to an earlier one.
+fn import_entry(
+    row: &HashMap<String, Value>,
+    ids_by_path: &HashMap<String, u64>,
+    paths_by_name: &HashMap<&str, Vec<&FilePath>>,
+) -> Option<HistoryEntry> {
+    let name = import_text(row, "name")?;
+    let opened_at = DateTime::parse_from_rfc3339(&import_text(row, "openedAt")?)
+        .ok()?
+        .with_timezone(&Utc);
+    let path = match import_text(row, "path") {
+        Some(p) => FilePath::Path(p.into()),
+        None => match paths_by_name.get(name.as_str())?.as_slice() {
+            [path] => (*path).clone(),
+            _ => return None,
+        },
+    };
+
+    let diagnostics = if row.get("randomnessLevel").is_some_and(|v| !v.is_null()) {
+        let fields: serde_json::Map<String, Value> = DIAGNOSTICS_COLUMNS[3..]
+            .iter()
+            .chain(EXTRA_COLUMNS[2..].iter())
+            .filter_map(|c| Some((c.to_string(), import_value(c, row.get(*c)?))))
+            .collect();
+        serde_json::from_value::<PickDiagnostics>(Value::Object(fields)).ok()
+    } else {
+        None
+    };
+
+    Some(HistoryEntry {
+        id: ids_by_path.get(&path_key(&path)).copied().unwrap_or(0),
+        name,
+        path,
+        opened_at,
+        diagnostics,
+        session_id: import_text(row, "sessionId"),
+    })
+}
+
+/// Exports carry millisecond timestamps, so match on millis
+fn dedup_key(entry: &HistoryEntry) -> (String, i64) {
+    (path_key(&entry.path), entry.opened_at.timestamp_millis())
+}
+
+/// Read a history or diagnostics export (CSV, JSON Lines or JSON) back into
+/// the history. Entries already present are skipped, and path pick counts
+/// are rebuilt from the imported entries. Returns None if the user cancelled.
+#[tauri::command]
+pub async fn import_history(
+    app: tauri::AppHandle,
+    app_data: State<'_, Mutex<AppStateData>>,
+    path_pick_counts: State<'_, PathPickCounts>,
+    mode: HistoryImportMode,
+) -> Result<Option<HistoryImportSummary>, String> {
+    // Dialog, read and parse on a blocking thread
+    let app_for_dialog = app.clone();
+    let rows = tauri::async_runtime::spawn_blocking(move || {
+        let picked = app_for_dialog
+            .dialog()
+            .file()
+            .add_filter("History export", &["csv", "jsonl", "json"])
+            .blocking_pick_file();
+        match picked {
+            Some(FilePath::Path(p)) => {
+                let content = std::fs::read_to_string(p).map_err(|e| e.to_string())?;
+                read_import_rows(&content).map(Some)
+            }
+            Some(FilePath::Url(_)) => Err("URL paths not supported for history import".to_string()),
+            None => Ok(None), // user cancelled
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    let Some(rows) = rows else {
+        return Ok(None);
+    };
+
+    let mut data = app_data.lock().unwrap();
+    let ids_by_path: HashMap<String, u64> = data
+        .files
+        .iter()
+        .map(|f| (path_key(&f.path), f.id))
+        .collect();
+    let mut paths_by_name: HashMap<&str, Vec<&FilePath>> = HashMap::new();
+    for f in &data.files {
+        paths_by_name
+            .entry(f.name.as_str())
+            .or_default()
+            .push(&f.path);
+    }
+
+    let mut summary = HistoryImportSummary {
+        rows: rows.len(),
+        ..Default::default()
+    };
+    let mut seen: HashSet<(String, i64)> = match mode {
+        HistoryImportMode::Merge => data.history.iter().map(dedup_key).collect(),
+        HistoryImportMode::Replace => HashSet::new(),
+    };
+    let mut added = Vec::new();
+    for row in &rows {
+        match import_entry(row, &ids_by_path, &paths_by_name) {
+            Some(entry) if seen.insert(dedup_key(&entry)) => added.push(entry),
+            Some(_) => summary.duplicates += 1,
+            None => summary.skipped += 1,
+        }
+    }
+    summary.imported = added.len();
+
+    // Counts for the imported entries, on top of the existing counts when merging
+    let mut counts = path_pick_counts.0.lock().unwrap();
+    if mode == HistoryImportMode::Replace {
+        // Nothing picked before the import counts as recent or can be undone
+        counts.clear();
+        data.history.clear();
+        data.recency_list.clear();
+        data.last_picked_id = None;
+        data.last_picked_index = None;
+        app.state::<PickUndoStack>().0.lock().unwrap().clear();
+    } else {
+        for f in &data.files {
+            if let Some(&count) = data.pick_counts.get(&f.id).filter(|c| **c > 0) {
+                counts.insert(path_key(&f.path), count);
+            }
+        }
+    }
+    for entry in &added {
+        *counts.entry(path_key(&entry.path)).or_insert(0) += 1;
+    }
+    data.pick_counts = ids_by_path
+        .iter()
+        .filter_map(|(path, id)| counts.get(path).map(|&c| (*id, c)))
+        .collect();
+    drop(counts);
+
+    data.history.extend(added);
+    data.history.sort_by_key(|h| h.opened_at);
+    summary.total = data.history.len();
+
+    rewrite_history(&app, &data.history)?;
+    save_persisted_stats(&app, &data);
+
+    Ok(Some(summary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn row(value: Value) -> HashMap<String, Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn parse_csv_handles_quoting_and_line_endings() {
+        let csv = "a,b,c\r\n\"x, y\",\"say \"\"hi\"\"\",\"two\nlines\"\r\n\r\n1,,3";
+        assert_eq!(
+            parse_csv(csv).unwrap(),
+            vec![
+                vec!["a", "b", "c"],
+                vec!["x, y", "say \"hi\"", "two\nlines"],
+                vec!["1", "", "3"],
+            ]
+        );
+        assert!(parse_csv("a,\"open").is_err());
+    }
+
+    #[test]
+    fn read_import_rows_detects_format() {
+        let csv = "\u{feff}\"name\",\"openedAt\"\n\"cat.png\",\"2026-01-02T03:04:05.006Z\"\n";
+        let jsonl = "{\"name\":\"cat.png\",\"pickNumber\":1}\n\n{\"name\":\"dog.png\"}\n";
+        let array = r#"[{"name":"cat.png"}]"#;
+
+        let rows = read_import_rows(csv).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["openedAt"], json!("2026-01-02T03:04:05.006Z"));
+
+        let rows = read_import_rows(jsonl).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["pickNumber"], json!(1));
+
+        assert_eq!(
+            read_import_rows(array).unwrap()[0]["name"],
+            json!("cat.png")
+        );
+
+        let err = read_import_rows("{\"name\":1}\n{oops").unwrap_err();
+        assert!(err.contains("line 2"), "{}", err);
+    }
+
+    #[test]
+    fn import_value_restores_types_outside_text_columns() {
+        assert_eq!(import_value("candidates", &json!("12")), json!(12));
+        assert_eq!(import_value("weightMean", &json!("0.5")), json!(0.5));
+        assert_eq!(
+            import_value("bookmarkPrefEnabled", &json!("true")),
+            json!(true)
+        );
+        assert_eq!(import_value("name", &json!("42")), json!("42"));
+        assert_eq!(import_value("weightMin", &json!("")), Value::Null);
+    }
+
+    #[test]
+    fn import_entry_resolves_paths_and_ids() {
+        let cat = FilePath::Path("/art/cat.png".into());
+        let ids_by_path = HashMap::from([("/art/cat.png".to_string(), 7)]);
+        let dog_a = FilePath::Path("/a/dog.png".into());
+        let dog_b = FilePath::Path("/b/dog.png".into());
+        let paths_by_name =
+            HashMap::from([("cat.png", vec![&cat]), ("dog.png", vec![&dog_a, &dog_b])]);
+        let opened_at = "2026-01-02T03:04:05.006Z";
+
+        let with_path = row(json!({
+            "name": "cat.png",
+            "path": "/art/cat.png",
+            "openedAt": opened_at,
+            "id": 99,
+        }));
+        let entry = import_entry(&with_path, &ids_by_path, &paths_by_name).unwrap();
+        assert_eq!(entry.id, 7);
+        assert_eq!(path_key(&entry.path), "/art/cat.png");
+
+        let by_name = row(json!({ "name": "cat.png", "openedAt": opened_at }));
+        let entry = import_entry(&by_name, &ids_by_path, &paths_by_name).unwrap();
+        assert_eq!(path_key(&entry.path), "/art/cat.png");
+
+        let unknown = row(json!({ "name": "bird.png", "openedAt": opened_at }));
+        assert!(import_entry(&unknown, &ids_by_path, &paths_by_name).is_none());
+
+        // A name found in several folders can't say which file was picked
+        let ambiguous = row(json!({ "name": "dog.png", "openedAt": opened_at }));
+        assert!(import_entry(&ambiguous, &ids_by_path, &paths_by_name).is_none());
+        let dog = row(json!({ "name": "dog.png", "path": "/b/dog.png", "openedAt": opened_at }));
+        let entry = import_entry(&dog, &ids_by_path, &paths_by_name).unwrap();
+        assert_eq!(path_key(&entry.path), "/b/dog.png");
+
+        let undated = row(json!({ "name": "cat.png", "path": "/art/cat.png" }));
+        assert!(import_entry(&undated, &ids_by_path, &paths_by_name).is_none());
+    }
+
+    #[test]
+    fn dedup_key_matches_on_path_and_millis() {
+        let entry = |opened_at: &str| HistoryEntry {
+            id: 1,
+            name: "cat.png".into(),
+            path: FilePath::Path("/art/cat.png".into()),
+            opened_at: DateTime::parse_from_rfc3339(opened_at)
+                .unwrap()
+                .with_timezone(&Utc),
+            diagnostics: None,
+            session_id: None,
+        };
+        assert_eq!(
+            dedup_key(&entry("2026-01-02T03:04:05.006123Z")),
+            dedup_key(&entry("2026-01-02T03:04:05.006Z"))
+        );
+        assert_ne!(
+            dedup_key(&entry("2026-01-02T03:04:05.006Z")),
+            dedup_key(&entry("2026-01-02T03:04:05.007Z"))
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use crate::filerandomisercommands::{prune_history, stats_file_path};
+use crate::models::HistoryEntry;
+use crate::persistence::{save_with_backups, write_atomic};
+use rayon::prelude::*;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+/// Pick history lives in history.jsonl, one compact JSON entry per line. A
+/// pick appends a single line; the whole file is only rewritten when
+/// compacting away entries that fell outside the retention window.
+pub(crate) fn history_log_path(app: &tauri::AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(|d| d.join("history.jsonl"))
+}
+
+fn to_line(entry: &HistoryEntry) -> Result<String, String> {
+    let mut line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+    line.push('\n');
+    Ok(line)
+}
+
+/// Append one entry to the log
+pub(crate) fn append_history(app: &tauri::AppHandle, entry: &HistoryEntry) -> Result<(), String> {
+    let path = history_log_path(app).ok_or("App data directory unavailable")?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| e.to_string())?;
+    file.write_all(to_line(entry)?.as_bytes())
+        .map_err(|e| e.to_string())?;
+    file.sync_data().map_err(|e| e.to_string())
+}
+
+/// Replace the log with exactly `entries`
+pub(crate) fn rewrite_history(
+    app: &tauri::AppHandle,
+    entries: &[HistoryEntry],
+) -> Result<(), String> {
+    let path = history_log_path(app).ok_or("App data directory unavailable")?;
+    write_atomic(&path, history_log_contents(entries)?.as_bytes())
+}
+
+/// The log file's contents for exactly `entries`
+pub(crate) fn history_log_contents(entries: &[HistoryEntry]) -> Result<String, String> {
+    let lines: Vec<String> = entries.par_iter().map(to_line).collect::<Result<_, _>>()?;
+    Ok(lines.concat())
+}
+
+/// Parse the log in parallel. Lines that fail to parse, such as a line torn
+/// by a crash mid-append, are skipped and counted.
+fn read_history(path: &Path) -> (Vec<HistoryEntry>, usize) {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return (vec![], 0);
+    };
+    let lines: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+    let mut entries: Vec<HistoryEntry> = lines
+        .par_iter()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect();
+    let skipped = lines.len() - entries.len();
+    entries.par_sort_by_key(|h| h.opened_at);
+    (entries, skipped)
+}
+
+/// Load history at startup. `legacy` is the history array from a stats file
+/// written before the log existed; it is moved into the log and stripped from
+/// the stats file. The log is compacted when entries were pruned, lines were
+/// unreadable, or legacy entries were merged in.
+pub(crate) fn load_history(
+    app: &tauri::AppHandle,
+    legacy: Vec<HistoryEntry>,
+    retention_days: u32,
+) -> Vec<HistoryEntry> {
+    let Some(path) = history_log_path(app) else {
+        return legacy;
+    };
+    let (mut history, skipped) = read_history(&path);
+    if skipped > 0 {
+        eprintln!(
+            "Skipped {} unreadable history entries in {:?}",
+            skipped, path
+        );
+    }
+
+    let migrating = !legacy.is_empty();
+    if migrating {
+        history.extend(legacy);
+        history.sort_by_key(|h| h.opened_at);
+        // A crash between writing the log and stripping the stats file would
+        // otherwise merge the legacy entries twice
+        history.dedup_by(|a, b| a.opened_at == b.opened_at && a.id == b.id);
+    }
+
+    let before = history.len();
+    prune_history(&mut history, retention_days);
+
+    if migrating || skipped > 0 || history.len() != before {
+        match rewrite_history(app, &history) {
+            Ok(()) if migrating => strip_legacy_history(app),
+            Ok(()) => {}
+            Err(e) => eprintln!("Failed to compact history log: {}", e),
+        }
+    }
+
+    history
+}
+
+/// Drop the `history` array from randomiser_stats.json once it is in the log
+fn strip_legacy_history(app: &tauri::AppHandle) {
+    let Some(path) = stats_file_path(app) else {
+        return;
+    };
+    let Ok(mut value) = std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).map_err(|e| e.to_string()))
+    else {
+        return;
+    };
+    let removed = value
+        .as_object_mut()
+        .and_then(|o| o.remove("history"))
+        .is_some();
+    if removed {
+        let saved = serde_json::to_string_pretty(&value)
+            .map_err(|e| e.to_string())
+            .and_then(|json| save_with_backups(&path, json.as_bytes()));
+        if let Err(e) = saved {
+            eprintln!("Failed to strip history from stats: {}", e);
+        }
+    }
+}
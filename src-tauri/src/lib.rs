@@ -1,12 +1,29 @@
-use crate::models::{AppStateData, FileSorterState, PersistedStats};
-use crate::filerandomisercommands::{prune_history, PathPickCounts};
+use crate::models::{AppStateData, FileSorterState};
+use crate::filerandomisercommands::{
+    flush_stats_save, load_persisted_stats, CrawlJob, PathFirstSeen, PathPickCounts, PickUndoStack,
+    StatsSavePending,
+};
 use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
 use std::sync::Mutex;
 use tauri::Manager;
+mod blendcommands;
+mod bookmarkcommands;
+mod constraints;
 mod fileauditorcommands;
 mod filerandomisercommands;
 mod filesortercommands;
+mod historycommands;
+mod historylog;
+mod metricscommands;
+mod migrations;
 pub mod models;
+mod openactions;
+mod persistence;
+mod presetcommands;
+mod relocatecommands;
+mod seriescommands;
+mod sessioncommands;
 pub mod setting_commands;
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
@@ -23,6 +40,20 @@ pub fn run() {
         .manage(models::settings::AppSettings::default())
         .manage(Mutex::new(AppStateData::default()))
         .manage(PathPickCounts(Mutex::new(HashMap::new())))
+        .manage(PathFirstSeen(Mutex::new(HashMap::new())))
+        .manage(PickUndoStack(Mutex::new(Vec::new())))
+        .manage(CrawlJob(Mutex::new(None)))
+        .manage(StatsSavePending(AtomicBool::new(false)))
+        .manage(sessioncommands::SessionsSavePending(AtomicBool::new(false)))
+        .manage(sessioncommands::PickSessions(Mutex::new(
+            sessioncommands::SessionState::new(vec![]),
+        )))
+        .manage(blendcommands::LoadedBlend(Mutex::new(None)))
+        .manage(bookmarkcommands::BookmarksWritable(AtomicBool::new(true)))
+        .manage(bookmarkcommands::BookmarkJournal(Mutex::new(0)))
+        .manage(bookmarkcommands::GlobalBookmarks(Mutex::new(
+            models::BookmarkIndex::default(),
+        )))
         .manage(filesortercommands::UndoStack(Mutex::new(Vec::new())))
         .manage(Mutex::new(FileSorterState::default()))
         .manage(fileauditorcommands::TrackedProcessMap(Mutex::new(
@@ -30,31 +61,30 @@ pub fn run() {
         )))
         .setup(|app| {
             let handle = app.handle().clone();
-            let stats_path = handle
-                .path()
-                .app_data_dir()
+            setting_commands::migrate_settings(&handle);
+            bookmarkcommands::load_global_bookmarks(&handle);
+            let stats = load_persisted_stats(&handle).unwrap_or_default();
+            let retention_days = setting_commands::get_app_settings(handle.clone())
                 .ok()
-                .map(|d: std::path::PathBuf| d.join("randomiser_stats.json"));
-            if let Some(path) = stats_path {
-                if let Ok(content) = std::fs::read_to_string(path) {
-                    if let Ok(stats) = serde_json::from_str::<PersistedStats>(&content) {
-                        let retention_days = setting_commands::get_app_settings(handle.clone())
-                            .ok()
-                            .map(|s| s.file_randomiser.history_retention_days)
-                            .unwrap_or(180);
-                        let mut history = stats.history;
-                        prune_history(&mut history, retention_days);
-                        {
-                            let app_data = handle.state::<Mutex<AppStateData>>();
-                            let mut data = app_data.lock().unwrap();
-                            data.history = history;
-                        }
-                        {
-                            let counts = handle.state::<PathPickCounts>();
-                            *counts.0.lock().unwrap() = stats.path_pick_counts;
-                        }
-                    }
-                }
+                .map(|s| s.file_randomiser.history_retention_days)
+                .unwrap_or(180);
+            let history = historylog::load_history(&handle, stats.history, retention_days);
+            {
+                let app_data = handle.state::<Mutex<AppStateData>>();
+                let mut data = app_data.lock().unwrap();
+                data.history = history;
+            }
+            {
+                let counts = handle.state::<PathPickCounts>();
+                *counts.0.lock().unwrap() = stats.path_pick_counts;
+            }
+            {
+                let first_seen = handle.state::<PathFirstSeen>();
+                *first_seen.0.lock().unwrap() = stats.path_first_seen;
+            }
+            {
+                let sessions = handle.state::<sessioncommands::PickSessions>();
+                sessions.0.lock().unwrap().sessions = sessioncommands::load_sessions(&handle);
             }
             Ok(())
         })
@@ -63,6 +93,7 @@ pub fn run() {
             // Settings
             setting_commands::get_app_settings,
             setting_commands::set_app_settings,
+            setting_commands::get_tuning_profiles,
             setting_commands::toggle_process_tracking,
             setting_commands::set_dark_mode,
             setting_commands::set_custom_background,
@@ -70,28 +101,60 @@ pub fn run() {
             setting_commands::set_randomness_level,
             setting_commands::restart_app,
             setting_commands::set_language,
-            setting_commands::get_global_bookmarks,
-            setting_commands::set_global_bookmarks,
             setting_commands::open_settings_folder,
             setting_commands::set_file_auditor_keybinds,
             setting_commands::toggle_auditor_process_tracking,
+            // Global bookmarks
+            bookmarkcommands::get_global_bookmarks,
+            bookmarkcommands::set_global_bookmarks,
+            bookmarkcommands::upsert_global_bookmarks,
+            bookmarkcommands::remove_global_bookmarks,
+            bookmarkcommands::update_global_bookmark,
             // File randomiser
             filerandomisercommands::get_app_state,
             filerandomisercommands::add_path_via_dialog,
             filerandomisercommands::remove_path,
             filerandomisercommands::crawl_paths,
+            filerandomisercommands::cancel_crawl,
             filerandomisercommands::pick_random_file,
+            filerandomisercommands::undo_last_pick,
             filerandomisercommands::open_file_by_id,
             filerandomisercommands::update_app_state,
-            filerandomisercommands::open_presets_folder,
-            filerandomisercommands::get_presets,
-            filerandomisercommands::save_preset,
             filerandomisercommands::open_path,
             filerandomisercommands::get_file_scores,
+            filerandomisercommands::explain_pick,
+            seriescommands::get_series_progress,
             filerandomisercommands::set_preset_path_weights,
             filerandomisercommands::update_file_bookmark,
             filerandomisercommands::update_file_bookmarks_bulk,
             filerandomisercommands::save_csv,
+            filerandomisercommands::get_path_variables,
+            // History
+            historycommands::query_history,
+            historycommands::export_history,
+            historycommands::import_history,
+            sessioncommands::start_pick_session,
+            sessioncommands::end_pick_session,
+            sessioncommands::set_active_preset,
+            sessioncommands::get_session_summaries,
+            metricscommands::get_randomiser_metrics,
+            // Library relocation
+            relocatecommands::relocate_library,
+            // Presets
+            presetcommands::open_presets_folder,
+            presetcommands::get_presets,
+            presetcommands::save_preset,
+            presetcommands::rename_preset,
+            presetcommands::delete_preset,
+            presetcommands::duplicate_preset,
+            presetcommands::export_preset_bundle,
+            presetcommands::open_preset_bundle,
+            presetcommands::import_preset_bundle,
+            // Preset blends
+            blendcommands::get_blends,
+            blendcommands::save_blend,
+            blendcommands::load_blend,
+            blendcommands::pick_blended_file,
             // File sorter
             filesortercommands::get_sorter_state,
             filesortercommands::select_sort_directory,
@@ -111,6 +174,12 @@ pub fn run() {
             fileauditorcommands::close_tracked_file,
             fileauditorcommands::delete_to_trash
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                flush_stats_save(handle);
+                sessioncommands::flush_sessions_save(handle);
+            }
+        });
 }
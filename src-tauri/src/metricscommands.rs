@@ -0,0 +1,296 @@
+use crate::filerandomisercommands::score_candidates;
+use crate::historycommands::{filter_history, parent_folder, path_key};
+use crate::models::{
+    AppStateData, CoverageMetrics, CoveragePoint, DistributionFit, FileEntry, GapBucket,
+    HistoryQuery, RandomiserMetrics, RepeatGapMetrics, StreakLengthCount, StreakMetrics,
+};
+use crate::setting_commands::get_app_settings;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+use tauri::State;
+
+/// Lower bounds of the repeat-gap buckets; the last bucket is open-ended
+const GAP_BUCKETS: &[usize] = &[1, 2, 3, 5, 10, 25, 50, 100, 250, 500, 1000];
+
+const MAX_CURVE_POINTS: usize = 200;
+
+/// Expected unique items after `k` uniform draws with replacement from `n`
+fn birthday_unique(n: usize, k: usize) -> f64 {
+    if n == 0 {
+        return 0.0;
+    }
+    let n = n as f64;
+    n * (1.0 - (1.0 - 1.0 / n).powf(k as f64))
+}
+
+fn coverage_metrics(picked: &[&str], pool_size: usize) -> CoverageMetrics {
+    let k = picked.len();
+    let step = k.div_ceil(MAX_CURVE_POINTS).max(1);
+    let mut seen = HashSet::new();
+    let mut curve = Vec::new();
+    for (i, path) in picked.iter().enumerate() {
+        seen.insert(*path);
+        let pick = i + 1;
+        if pick % step == 0 || pick == k || pick == 1 {
+            curve.push(CoveragePoint {
+                pick,
+                unique: seen.len(),
+                baseline: birthday_unique(pool_size, pick),
+            });
+        }
+    }
+
+    let baseline_unique = birthday_unique(pool_size, k);
+    let fraction = |v: f64| {
+        if pool_size > 0 {
+            v / pool_size as f64
+        } else {
+            0.0
+        }
+    };
+    CoverageMetrics {
+        unique_files: seen.len(),
+        coverage: fraction(seen.len() as f64),
+        baseline_unique,
+        baseline_coverage: fraction(baseline_unique),
+        actual_repeats: k - seen.len(),
+        expected_repeats: k as f64 - baseline_unique,
+        curve,
+    }
+}
+
+fn repeat_gap_metrics(picked: &[&str], pool_size: usize) -> RepeatGapMetrics {
+    let mut last_seen: HashMap<&str, usize> = HashMap::new();
+    let mut counts = vec![0usize; GAP_BUCKETS.len()];
+    let mut gap_sum = 0usize;
+    let mut gap_count = 0usize;
+
+    for (i, path) in picked.iter().enumerate() {
+        if let Some(prev) = last_seen.insert(*path, i) {
+            let gap = i - prev;
+            let bucket = GAP_BUCKETS.partition_point(|&min| min <= gap) - 1;
+            counts[bucket] += 1;
+            gap_sum += gap;
+            gap_count += 1;
+        }
+    }
+
+    RepeatGapMetrics {
+        histogram: GAP_BUCKETS
+            .iter()
+            .enumerate()
+            .map(|(i, &min)| GapBucket {
+                min,
+                max: GAP_BUCKETS.get(i + 1).map(|next| next - 1),
+                count: counts[i],
+            })
+            .collect(),
+        mean_gap: (gap_count > 0).then(|| gap_sum as f64 / gap_count as f64),
+        expected_mean_gap: pool_size as f64,
+    }
+}
+
+/// Chi-square and KL divergence of the observed pick counts against each
+/// file's static weight (bookmark factor × path weight). Recency, coverage,
+/// order and streak factors shift from pick to pick to even things out, so
+/// they are not part of the long-run expectation.
+fn distribution_fit(observed: &[usize], expected_weights: &[f64]) -> Option<DistributionFit> {
+    let total_weight: f64 = expected_weights.iter().sum();
+    let unexpected_picks: usize = observed
+        .iter()
+        .zip(expected_weights)
+        .filter(|(_, w)| **w <= 0.0)
+        .map(|(o, _)| *o)
+        .sum();
+    let picks = observed.iter().sum::<usize>() - unexpected_picks;
+    let weighted = expected_weights.iter().filter(|w| **w > 0.0).count();
+    if picks == 0 || weighted < 2 || total_weight <= 0.0 {
+        return None;
+    }
+
+    let mut chi_square = 0.0;
+    let mut kl_divergence = 0.0;
+    for (&o, &w) in observed.iter().zip(expected_weights) {
+        if w <= 0.0 {
+            continue;
+        }
+        let q = w / total_weight;
+        let expected = picks as f64 * q;
+        chi_square += (o as f64 - expected).powi(2) / expected;
+        if o > 0 {
+            let p = o as f64 / picks as f64;
+            kl_divergence += p * (p / q).ln();
+        }
+    }
+
+    let degrees_of_freedom = weighted - 1;
+    Some(DistributionFit {
+        chi_square,
+        degrees_of_freedom,
+        z_score: (chi_square - degrees_of_freedom as f64)
+            / (2.0 * degrees_of_freedom as f64).sqrt(),
+        kl_divergence,
+        unexpected_picks,
+    })
+}
+
+/// Lengths of runs of equal consecutive keys. `None` keys break a run and
+/// are not counted, so uncoloured picks never form a colour streak.
+pub(crate) fn streak_metrics<'a>(keys: impl Iterator<Item = Option<&'a str>>) -> StreakMetrics {
+    let mut runs: Vec<usize> = Vec::new();
+    let mut current: Option<&str> = None;
+    let mut length = 0usize;
+    for key in keys {
+        if key.is_some() && key == current {
+            length += 1;
+            continue;
+        }
+        if current.is_some() {
+            runs.push(length);
+        }
+        current = key;
+        length = 1;
+    }
+    if current.is_some() {
+        runs.push(length);
+    }
+
+    let mut lengths: BTreeMap<usize, usize> = BTreeMap::new();
+    for run in &runs {
+        *lengths.entry(*run).or_insert(0) += 1;
+    }
+    StreakMetrics {
+        longest: runs.iter().copied().max().unwrap_or(0),
+        mean_length: if runs.is_empty() {
+            0.0
+        } else {
+            runs.iter().sum::<usize>() as f64 / runs.len() as f64
+        },
+        lengths: lengths
+            .into_iter()
+            .map(|(length, count)| StreakLengthCount { length, count })
+            .collect(),
+    }
+}
+
+/// Coverage, repeat-gap, goodness-of-fit and streak statistics over the pick
+/// history, as described in docs/randomness-paper.md. Only picks of files in
+/// the current pool count; the range is `from` inclusive to `to` exclusive.
+#[tauri::command]
+pub fn get_randomiser_metrics(
+    app: tauri::AppHandle,
+    app_data: State<'_, Mutex<AppStateData>>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<RandomiserMetrics, String> {
+    let settings = get_app_settings(app)?;
+    let data = app_data.lock().unwrap();
+
+    let candidates: Vec<(usize, &FileEntry)> = data
+        .files
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| !f.excluded)
+        .collect();
+    let pool_size = candidates.len();
+    let pool_index: HashMap<String, usize> = candidates
+        .iter()
+        .enumerate()
+        .map(|(i, (_, f))| (path_key(&f.path), i))
+        .collect();
+
+    let query = HistoryQuery {
+        from,
+        to,
+        ..Default::default()
+    };
+    let rows = filter_history(&data, &query);
+    let in_pool: Vec<(usize, String, Option<String>)> = rows
+        .iter()
+        .filter_map(|(_, entry, color)| {
+            let path = path_key(&entry.path);
+            let index = *pool_index.get(&path)?;
+            Some((index, path, color.clone()))
+        })
+        .collect();
+    let picked: Vec<&str> = in_pool.iter().map(|(_, p, _)| p.as_str()).collect();
+
+    let mut observed = vec![0usize; pool_size];
+    for (index, _, _) in &in_pool {
+        observed[*index] += 1;
+    }
+    let fit = if pool_size > 0 {
+        let scores = score_candidates(&candidates, &data, &settings, &data.preset_path_weights);
+        let expected_weights: Vec<f64> = scores
+            .bookmark_factors
+            .iter()
+            .zip(&scores.path_factors)
+            .map(|(b, p)| b * p)
+            .collect();
+        distribution_fit(&observed, &expected_weights)
+    } else {
+        None
+    };
+
+    let folders: Vec<String> = in_pool
+        .iter()
+        .map(|(index, _, _)| parent_folder(&candidates[*index].1.path))
+        .collect();
+
+    Ok(RandomiserMetrics {
+        picks: in_pool.len(),
+        picks_outside_pool: rows.len() - in_pool.len(),
+        pool_size,
+        coverage: coverage_metrics(&picked, pool_size),
+        repeat_gaps: repeat_gap_metrics(&picked, pool_size),
+        fit,
+        folder_streaks: streak_metrics(folders.iter().map(|f| Some(f.as_str()))),
+        color_streaks: streak_metrics(in_pool.iter().map(|(_, _, c)| c.as_deref())),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lengths(metrics: &StreakMetrics) -> Vec<(usize, usize)> {
+        metrics
+            .lengths
+            .iter()
+            .map(|l| (l.length, l.count))
+            .collect()
+    }
+
+    #[test]
+    fn streak_metrics_counts_runs_of_equal_keys() {
+        let keys = [
+            Some("red"),
+            Some("red"),
+            Some("blue"),
+            Some("red"),
+            Some("red"),
+            Some("red"),
+        ];
+        let metrics = streak_metrics(keys.into_iter());
+        assert_eq!(metrics.longest, 3);
+        assert_eq!(metrics.mean_length, 2.0);
+        assert_eq!(lengths(&metrics), [(1, 1), (2, 1), (3, 1)]);
+    }
+
+    #[test]
+    fn streak_metrics_lets_none_break_runs_without_counting() {
+        let keys = [Some("red"), None, Some("red"), None, None];
+        let metrics = streak_metrics(keys.into_iter());
+        assert_eq!(metrics.longest, 1);
+        assert_eq!(lengths(&metrics), [(1, 2)]);
+    }
+
+    #[test]
+    fn streak_metrics_is_empty_without_keys() {
+        let metrics = streak_metrics([None, None].into_iter());
+        assert_eq!(metrics.longest, 0);
+        assert_eq!(metrics.mean_length, 0.0);
+        assert!(metrics.lengths.is_empty());
+    }
+}
@@ -0,0 +1,257 @@
+use crate::persistence::write_atomic;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+pub const PRESET_SCHEMA_VERSION: u32 = 1;
+pub const STATS_SCHEMA_VERSION: u32 = 2;
+pub const SETTINGS_SCHEMA_VERSION: u32 = 1;
+pub const SESSIONS_SCHEMA_VERSION: u32 = 1;
+pub const BOOKMARKS_SCHEMA_VERSION: u32 = 1;
+
+type MigrationStep = fn(&mut Value) -> Result<(), String>;
+
+/// The persisted JSON formats that carry a schema version
+#[derive(Debug, Clone, Copy)]
+pub enum SchemaKind {
+    Preset,
+    Stats,
+    Settings,
+    Sessions,
+    Bookmarks,
+}
+
+impl SchemaKind {
+    /// PersistedStats is the one format serialised with snake_case keys
+    fn version_key(self) -> &'static str {
+        match self {
+            SchemaKind::Stats => "schema_version",
+            SchemaKind::Preset
+            | SchemaKind::Settings
+            | SchemaKind::Sessions
+            | SchemaKind::Bookmarks => "schemaVersion",
+        }
+    }
+
+    pub fn current(self) -> u32 {
+        match self {
+            SchemaKind::Preset => PRESET_SCHEMA_VERSION,
+            SchemaKind::Stats => STATS_SCHEMA_VERSION,
+            SchemaKind::Settings => SETTINGS_SCHEMA_VERSION,
+            SchemaKind::Sessions => SESSIONS_SCHEMA_VERSION,
+            SchemaKind::Bookmarks => BOOKMARKS_SCHEMA_VERSION,
+        }
+    }
+
+    /// `steps()[n]` upgrades a value from version n to n + 1
+    fn steps(self) -> &'static [MigrationStep] {
+        match self {
+            SchemaKind::Preset => &[preset_v0_to_v1],
+            SchemaKind::Stats => &[stats_v0_to_v1, stats_v1_to_v2],
+            SchemaKind::Settings => &[settings_v0_to_v1],
+            SchemaKind::Sessions => &[sessions_v0_to_v1],
+            SchemaKind::Bookmarks => &[bookmarks_v0_to_v1],
+        }
+    }
+}
+
+fn object_mut<'a>(
+    value: &'a mut Value,
+    what: &str,
+) -> Result<&'a mut serde_json::Map<String, Value>, String> {
+    value
+        .as_object_mut()
+        .ok_or_else(|| format!("{} is not a JSON object", what))
+}
+
+/// v1 adds the version field and fills the collections older builds could omit
+fn preset_v0_to_v1(value: &mut Value) -> Result<(), String> {
+    let preset = object_mut(value, "Preset")?;
+    preset
+        .entry("bookmarks")
+        .or_insert_with(|| Value::Array(vec![]));
+    preset
+        .entry("pathWeights")
+        .or_insert_with(|| Value::Object(Default::default()));
+    Ok(())
+}
+
+/// v1 adds the version field; `recency_list_paths` predates it as optional
+fn stats_v0_to_v1(value: &mut Value) -> Result<(), String> {
+    let stats = object_mut(value, "Stats")?;
+    stats
+        .entry("recency_list_paths")
+        .or_insert_with(|| Value::Array(vec![]));
+    Ok(())
+}
+
+/// v2 moves history to history.jsonl. The `history` array is left in place
+/// here so the pre-migration backup holds it; the history loader copies it
+/// into the log and then strips it from the stats file.
+fn stats_v1_to_v2(_value: &mut Value) -> Result<(), String> {
+    Ok(())
+}
+
+/// v1 adds the version field
+fn settings_v0_to_v1(value: &mut Value) -> Result<(), String> {
+    object_mut(value, "Settings")?;
+    Ok(())
+}
+
+/// v1 adds the version field
+fn sessions_v0_to_v1(value: &mut Value) -> Result<(), String> {
+    object_mut(value, "Sessions")?;
+    Ok(())
+}
+
+/// v1 adds the version field
+fn bookmarks_v0_to_v1(value: &mut Value) -> Result<(), String> {
+    object_mut(value, "Bookmarks")?;
+    Ok(())
+}
+
+pub fn schema_version(kind: SchemaKind, value: &Value) -> u32 {
+    value
+        .get(kind.version_key())
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u32
+}
+
+/// Run every step from the value's version up to the current one and stamp
+/// the new version. Returns the version the value started at.
+pub fn migrate_value(kind: SchemaKind, value: &mut Value) -> Result<u32, String> {
+    let from = schema_version(kind, value);
+    for version in from..kind.current() {
+        let step = kind
+            .steps()
+            .get(version as usize)
+            .ok_or_else(|| format!("No {:?} migration from version {}", kind, version))?;
+        step(value)?;
+        object_mut(value, "Migrated value")?
+            .insert(kind.version_key().to_string(), Value::from(version + 1));
+    }
+    Ok(from)
+}
+
+/// Copy `path` to `<file name>.v<version>.bak` beside it. An existing backup of
+/// the same version is kept, since it holds the oldest copy of that data.
+pub fn backup_file(path: &Path, version: u32) -> Result<PathBuf, String> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("Not a file: {:?}", path))?
+        .to_string_lossy();
+    let backup = path.with_file_name(format!("{}.v{}.bak", file_name, version));
+    if !backup.exists() {
+        std::fs::copy(path, &backup).map_err(|e| e.to_string())?;
+    }
+    Ok(backup)
+}
+
+/// Read a persisted JSON file, upgrading it to the current schema first when
+/// it is older. The original is backed up before the migrated file replaces
+/// it; if the backup fails the file is left untouched and an error returned.
+/// Files from a newer app version are read as-is and never rewritten.
+pub fn load_migrated<T: DeserializeOwned>(path: &Path, kind: SchemaKind) -> Result<T, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut value: Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+
+    if schema_version(kind, &value) < kind.current() {
+        let from = schema_version(kind, &value);
+        backup_file(path, from)?;
+        migrate_value(kind, &mut value)?;
+        let json = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+        write_atomic(path, json.as_bytes())?;
+    }
+
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "migrations-test-{}",
+            crate::models::common::random_id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn migrates_unversioned_preset_to_current() {
+        let mut value = json!({ "id": "p1", "name": "Art" });
+        let from = migrate_value(SchemaKind::Preset, &mut value).unwrap();
+        assert_eq!(from, 0);
+        assert_eq!(value["schemaVersion"], json!(PRESET_SCHEMA_VERSION));
+        assert_eq!(value["bookmarks"], json!([]));
+        assert_eq!(value["pathWeights"], json!({}));
+    }
+
+    #[test]
+    fn stats_use_snake_case_version_key() {
+        let mut value = json!({ "pick_counts": {} });
+        migrate_value(SchemaKind::Stats, &mut value).unwrap();
+        assert_eq!(value["schema_version"], json!(STATS_SCHEMA_VERSION));
+        assert_eq!(value["recency_list_paths"], json!([]));
+        assert!(value.get("schemaVersion").is_none());
+    }
+
+    #[test]
+    fn runs_only_the_missing_steps() {
+        let mut value = json!({ "schema_version": 1, "recency_list_paths": ["/a"] });
+        let from = migrate_value(SchemaKind::Stats, &mut value).unwrap();
+        assert_eq!(from, 1);
+        assert_eq!(value["schema_version"], json!(2));
+        assert_eq!(value["recency_list_paths"], json!(["/a"]));
+    }
+
+    #[test]
+    fn leaves_current_and_newer_versions_alone() {
+        for version in [SETTINGS_SCHEMA_VERSION, SETTINGS_SCHEMA_VERSION + 5] {
+            let mut value = json!({ "schemaVersion": version, "theme": "dark" });
+            let before = value.clone();
+            assert_eq!(migrate_value(SchemaKind::Settings, &mut value), Ok(version));
+            assert_eq!(value, before);
+        }
+    }
+
+    #[test]
+    fn rejects_values_that_are_not_objects() {
+        let mut value = json!([1, 2, 3]);
+        assert!(migrate_value(SchemaKind::Bookmarks, &mut value).is_err());
+    }
+
+    #[test]
+    fn load_migrated_backs_up_and_rewrites_old_files() {
+        let dir = temp_dir();
+        let path = dir.join("preset.json");
+        std::fs::write(&path, r#"{"id":"p1"}"#).unwrap();
+
+        let value: Value = load_migrated(&path, SchemaKind::Preset).unwrap();
+        assert_eq!(value["schemaVersion"], json!(PRESET_SCHEMA_VERSION));
+
+        let backup = std::fs::read_to_string(dir.join("preset.json.v0.bak")).unwrap();
+        assert_eq!(backup, r#"{"id":"p1"}"#);
+        let rewritten: Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(rewritten, value);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn backup_file_keeps_the_oldest_copy() {
+        let dir = temp_dir();
+        let path = dir.join("stats.json");
+        std::fs::write(&path, "first").unwrap();
+        backup_file(&path, 1).unwrap();
+        std::fs::write(&path, "second").unwrap();
+        let backup = backup_file(&path, 1).unwrap();
+        assert_eq!(std::fs::read_to_string(backup).unwrap(), "first");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,6 +1,7 @@
+use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
-use serde::{Deserialize, Serialize};
 use tauri_plugin_dialog::FilePath;
 
 pub fn hash_from_meta(meta: &std::fs::Metadata) -> u64 {
@@ -42,3 +43,277 @@ pub struct Bookmark {
     #[serde(default)]
     pub color: Option<String>,
 }
+
+/// Bookmarks keyed by lowercase content hash, so lookups during a crawl are
+/// O(1) instead of a scan over the whole list per file.
+#[derive(Debug, Clone, Default)]
+pub struct BookmarkIndex {
+    by_hash: HashMap<String, Bookmark>,
+}
+
+impl BookmarkIndex {
+    pub fn get(&self, hash: &str) -> Option<&Bookmark> {
+        self.by_hash.get(&hash.to_lowercase())
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_hash.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_hash.is_empty()
+    }
+
+    /// Insert or replace the bookmark for its hash
+    pub fn upsert(&mut self, bookmark: Bookmark) {
+        self.by_hash.insert(bookmark.hash.to_lowercase(), bookmark);
+    }
+
+    pub fn remove(&mut self, hash: &str) -> Option<Bookmark> {
+        self.by_hash.remove(&hash.to_lowercase())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Bookmark> {
+        self.by_hash.values()
+    }
+
+    /// All bookmarks, sorted by path so the persisted file diffs cleanly
+    pub fn to_vec(&self) -> Vec<Bookmark> {
+        let mut list: Vec<Bookmark> = self.by_hash.values().cloned().collect();
+        list.sort_by_key(|b| b.path.to_string());
+        list
+    }
+}
+
+impl From<Vec<Bookmark>> for BookmarkIndex {
+    fn from(bookmarks: Vec<Bookmark>) -> Self {
+        let mut index = BookmarkIndex::default();
+        for bm in bookmarks {
+            index.upsert(bm);
+        }
+        index
+    }
+}
+
+/// Rewrite `path` when it lies under the directory `from`, matching whole path
+/// components only ("C:\Art" does not match "C:\Artwork"). Separators in the
+/// remainder follow the style of `to`, so Windows paths can move to Unix.
+pub fn remap_path_prefix(path: &str, from: &str, to: &str) -> Option<String> {
+    let from = from.trim_end_matches(['/', '\\']);
+    let rest = path.strip_prefix(from)?;
+    if !(rest.is_empty() || rest.starts_with(['/', '\\'])) {
+        return None;
+    }
+
+    let to = to.trim_end_matches(['/', '\\']);
+    let rest = if to.contains('\\') && !to.contains('/') {
+        rest.replace('/', "\\")
+    } else if to.contains('/') && !to.contains('\\') {
+        rest.replace('\\', "/")
+    } else {
+        rest.to_string()
+    };
+    Some(format!("{}{}", to, rest))
+}
+
+/// Split off the leading run of digits or of non-digits
+fn split_chunk(s: &str) -> (&str, &str) {
+    let digits = s.starts_with(|c: char| c.is_ascii_digit());
+    let end = s
+        .find(|c: char| c.is_ascii_digit() != digits)
+        .unwrap_or(s.len());
+    s.split_at(end)
+}
+
+/// Compare names the way a file manager orders them: runs of digits compare
+/// by value, so "Episode 2" sorts before "Episode 10", and letters ignore case
+pub fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    let (mut rest_a, mut rest_b) = (a, b);
+    while !rest_a.is_empty() && !rest_b.is_empty() {
+        let (chunk_a, next_a) = split_chunk(rest_a);
+        let (chunk_b, next_b) = split_chunk(rest_b);
+        let numeric = |c: &str| c.starts_with(|c: char| c.is_ascii_digit());
+        let order = if numeric(chunk_a) && numeric(chunk_b) {
+            let num_a = chunk_a.trim_start_matches('0');
+            let num_b = chunk_b.trim_start_matches('0');
+            num_a
+                .len()
+                .cmp(&num_b.len())
+                .then_with(|| num_a.cmp(num_b))
+                .then_with(|| chunk_a.len().cmp(&chunk_b.len()))
+        } else {
+            chunk_a.to_lowercase().cmp(&chunk_b.to_lowercase())
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+        rest_a = next_a;
+        rest_b = next_b;
+    }
+    rest_a.len().cmp(&rest_b.len()).then_with(|| a.cmp(b))
+}
+
+/// Random UUID-v4 style identifier, matching the ids the frontend generates
+pub fn random_id() -> String {
+    let bytes: [u8; 16] = rand::random();
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-4{}-{:x}{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[13..16],
+        (bytes[8] & 0x3) | 0x8,
+        &hex[17..20],
+        &hex[20..32]
+    )
+}
+
+/// Resolved `${NAME}` path variables. Built-ins (HOME, PICTURES, ...) come from
+/// the OS, user-defined roots from settings. Names are case-insensitive.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PathVariables {
+    values: HashMap<String, String>,
+}
+
+impl PathVariables {
+    pub fn insert(&mut self, name: &str, value: String) {
+        self.values.insert(name.to_uppercase(), value);
+    }
+
+    pub fn values(&self) -> &HashMap<String, String> {
+        &self.values
+    }
+
+    /// Replace every known `${NAME}` in `input`. Unknown variables are left as
+    /// written so the path simply fails to exist rather than pointing elsewhere.
+    pub fn expand(&self, input: &str) -> String {
+        let mut out = String::with_capacity(input.len());
+        let mut rest = input;
+        while let Some(start) = rest.find("${") {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let Some(end) = after.find('}') else {
+                out.push_str(&rest[start..]);
+                return out;
+            };
+            match self.values.get(&after[..end].to_uppercase()) {
+                Some(value) => out.push_str(value),
+                None => out.push_str(&rest[start..start + end + 3]),
+            }
+            rest = &after[end + 1..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    pub fn expand_file_path(&self, path: &FilePath) -> FilePath {
+        match path {
+            FilePath::Path(p) => match p.to_str() {
+                Some(s) if s.contains("${") => FilePath::Path(self.expand(s).into()),
+                _ => path.clone(),
+            },
+            FilePath::Url(_) => path.clone(),
+        }
+    }
+
+    pub fn expand_saved_paths(&self, paths: &[SavedPath]) -> Vec<SavedPath> {
+        paths
+            .iter()
+            .map(|p| SavedPath {
+                path: self.expand_file_path(&p.path),
+                ..p.clone()
+            })
+            .collect()
+    }
+
+    pub fn expand_keys(&self, weights: &HashMap<String, f64>) -> HashMap<String, f64> {
+        weights.iter().map(|(k, v)| (self.expand(k), *v)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remap_replaces_matching_prefix() {
+        assert_eq!(
+            remap_path_prefix("/home/a/Art/cat.png", "/home/a/Art", "/mnt/art"),
+            Some("/mnt/art/cat.png".to_string())
+        );
+        assert_eq!(
+            remap_path_prefix("/home/a/Art", "/home/a/Art/", "/mnt/art/"),
+            Some("/mnt/art".to_string())
+        );
+    }
+
+    #[test]
+    fn remap_matches_whole_components_only() {
+        assert_eq!(
+            remap_path_prefix(r"C:\Artwork\cat.png", r"C:\Art", "/mnt/art"),
+            None
+        );
+        assert_eq!(remap_path_prefix("/other/cat.png", "/home", "/mnt"), None);
+    }
+
+    #[test]
+    fn remap_converts_separators_to_target_style() {
+        assert_eq!(
+            remap_path_prefix(r"C:\Art\cats\cat.png", r"C:\Art", "/mnt/art"),
+            Some("/mnt/art/cats/cat.png".to_string())
+        );
+        assert_eq!(
+            remap_path_prefix("/home/a/Art/cats/cat.png", "/home/a/Art", r"D:\Art"),
+            Some(r"D:\Art\cats\cat.png".to_string())
+        );
+    }
+
+    fn variables() -> PathVariables {
+        let mut vars = PathVariables::default();
+        vars.insert("home", "/home/a".to_string());
+        vars.insert("ART", "/mnt/art".to_string());
+        vars
+    }
+
+    #[test]
+    fn expand_replaces_known_variables_case_insensitively() {
+        let vars = variables();
+        assert_eq!(vars.expand("${HOME}/Pictures"), "/home/a/Pictures");
+        assert_eq!(vars.expand("${art}/${Home}"), "/mnt/art//home/a");
+    }
+
+    #[test]
+    fn expand_leaves_unknown_and_unterminated_variables() {
+        let vars = variables();
+        assert_eq!(vars.expand("${NOPE}/x"), "${NOPE}/x");
+        assert_eq!(vars.expand("${HOME}/${ART"), "/home/a/${ART");
+        assert_eq!(vars.expand("plain/path"), "plain/path");
+    }
+
+    #[test]
+    fn expand_keys_expands_weight_paths() {
+        let weights = HashMap::from([("${ART}/cats".to_string(), 2.0)]);
+        let expanded = variables().expand_keys(&weights);
+        assert_eq!(expanded.get("/mnt/art/cats"), Some(&2.0));
+    }
+
+    #[test]
+    fn natural_cmp_orders_numbers_by_value() {
+        let mut names = vec!["Episode 10", "episode 2", "Episode 1", "Episode 02b"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(
+            names,
+            ["Episode 1", "episode 2", "Episode 02b", "Episode 10"]
+        );
+    }
+
+    #[test]
+    fn natural_cmp_breaks_ties_deterministically() {
+        use std::cmp::Ordering;
+        assert_eq!(natural_cmp("a2", "a02"), Ordering::Less);
+        assert_eq!(natural_cmp("Cat", "cat"), Ordering::Less);
+        assert_eq!(natural_cmp("cat", "cat 1"), Ordering::Less);
+        assert_eq!(natural_cmp("cat", "cat"), Ordering::Equal);
+    }
+}
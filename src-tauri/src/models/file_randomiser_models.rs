@@ -4,7 +4,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tauri_plugin_dialog::FilePath;
 
-use crate::models::{Bookmark, SavedPath};
+use crate::models::settings::FileRandomiserSettings;
+use crate::models::{Bookmark, PathVariables, SavedPath};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -19,6 +20,13 @@ pub struct FileEntry {
 
     #[serde(default)]
     pub bookmark: Option<BookmarkInfo>,
+
+    /// When a crawl first found this path
+    #[serde(default)]
+    pub first_seen: Option<DateTime<Utc>>,
+
+    #[serde(default)]
+    pub modified: Option<DateTime<Utc>>,
 }
 
 #[derive(Serialize, Clone)]
@@ -31,9 +39,62 @@ pub struct FileScore {
     pub memory_factor: f64,
     pub bookmark_factor: f64,
     pub coverage_factor: f64,
+    pub pick_count: u32,
+    /// Pick count after half-life decay, as used by the coverage factor
+    pub decayed_pick_count: f64,
+    pub new_arrival_factor: f64,
     pub total_weight: f64,
 }
 
+/// How far through one folder series mode is
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesProgress {
+    pub folder: String,
+    pub total: usize,
+    pub seen: usize,
+    /// Name of the file series mode would open next, None once all are seen
+    pub next: Option<String>,
+    pub last_opened_at: Option<DateTime<Utc>>,
+}
+
+/// Every weighting factor for one candidate; the weight is their product,
+/// or 0 when hard-blocked
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PickFactors {
+    pub order_score: f64,
+    pub memory_factor: f64,
+    pub bookmark_factor: f64,
+    pub path_factor: f64,
+    pub coverage_factor: f64,
+    pub decayed_pick_count: f64,
+    pub new_arrival_factor: f64,
+    pub color_streak_factor: f64,
+    pub folder_streak_factor: f64,
+    pub hard_blocked: bool,
+    pub weight: f64,
+}
+
+/// Why a file is as likely as it is to come up on the next pick
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PickExplanation {
+    pub id: u64,
+    pub name: String,
+    pub excluded: bool,
+    pub candidates: usize,
+    pub pick_count: u32,
+    pub tuning_profile: String,
+    /// None when the file is excluded and so never a candidate
+    pub factors: Option<PickFactors>,
+    pub probability: f64,
+    /// 1 for the most likely candidate; ties share a rank
+    pub rank: Option<usize>,
+    /// Mean picks until it comes up if the weights stayed as they are
+    pub expected_picks_until_seen: Option<f64>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct HistoryEntry {
@@ -43,6 +104,17 @@ pub struct HistoryEntry {
     pub opened_at: DateTime<Utc>,
     #[serde(default)]
     pub diagnostics: Option<PickDiagnostics>,
+    /// Pick session the entry belongs to; None for files opened by hand
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+}
+
+/// Payload of the "crawl-progress" event: what a running crawl has walked
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CrawlProgress {
+    pub directories: u64,
+    pub files: u64,
 }
 
 /// Per-pick algorithm diagnostics. Captured on every randomiser pick so the
@@ -72,13 +144,37 @@ pub struct PickDiagnostics {
     pub chosen_folder_streak_factor: f64,
     pub chosen_bookmark_color: Option<String>,
     pub chosen_bookmark_global: bool,
+    /// Set when the pick came from a preset blend
+    #[serde(default)]
+    pub blend_id: Option<String>,
+    #[serde(default)]
+    pub blend_preset_id: Option<String>,
+    /// Chance the blend had of choosing this preset, after renormalising
+    /// over presets that had candidates
+    #[serde(default)]
+    pub blend_preset_probability: Option<f64>,
+    /// Algorithm tuning profile active for the pick
+    #[serde(default)]
+    pub tuning_profile: Option<String>,
+    /// Folder a series-mode pick continued or started
+    #[serde(default)]
+    pub series_folder: Option<String>,
+    /// Session constraints enforced for the pick
+    #[serde(default)]
+    pub constraints_applied: u32,
+    /// Constraints that had to be relaxed and that the pick then broke
+    #[serde(default)]
+    pub constraint_violations: u32,
+    /// Files chosen first but found deleted or moved, and skipped
+    #[serde(default)]
+    pub skipped_missing: u32,
 }
 
 fn default_streak_factor() -> f64 {
     1.0
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct AppStateData {
     pub paths: Vec<SavedPath>,
@@ -92,28 +188,16 @@ pub struct AppStateData {
     pub recency_list: Vec<u64>,
     #[serde(default)]
     pub preset_path_weights: HashMap<String, f64>,
-}
-
-impl Default for AppStateData {
-    fn default() -> Self {
-        Self {
-            paths: vec![],
-            files: vec![],
-            history: vec![],
-            tracking_enabled: false,
-            filter_rules: vec![],
-            last_picked_id: None,
-            last_picked_index: None,
-            pick_counts: HashMap::new(),
-            recency_list: vec![],
-            preset_path_weights: HashMap::new(),
-        }
-    }
+    /// Variables resolved at the last crawl, used to expand path-weight keys
+    #[serde(skip)]
+    pub path_variables: PathVariables,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct RandomiserPreset {
+    #[serde(default)]
+    pub schema_version: u32,
     pub id: String,
     pub name: String,
     pub paths: Vec<SavedPath>,
@@ -125,6 +209,58 @@ pub struct RandomiserPreset {
     pub path_weights: HashMap<String, f64>,
 }
 
+/// A preset or blend file that failed to load
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InvalidPresetFile {
+    pub file_name: String,
+    pub error: String,
+}
+
+/// Presets on disk, plus any files in the presets folder that failed to load
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetListing {
+    pub presets: Vec<RandomiserPreset>,
+    pub invalid: Vec<InvalidPresetFile>,
+}
+
+/// One preset inside a blend. Weights are relative shares, e.g. 70 and 30.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BlendComponent {
+    pub preset_id: String,
+    pub weight: f64,
+}
+
+/// A weighted mix of presets. Each pick first samples a preset by weight, then
+/// a file within it using that preset's own paths, filters, bookmarks and
+/// path weights.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetBlend {
+    pub id: String,
+    pub name: String,
+    pub components: Vec<BlendComponent>,
+}
+
+/// Blends on disk, plus any files in the blends folder that failed to load
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BlendListing {
+    pub blends: Vec<PresetBlend>,
+    pub invalid: Vec<InvalidPresetFile>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BlendPick {
+    pub file: FileEntry,
+    pub preset_id: String,
+    pub preset_name: String,
+    pub preset_probability: f64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum FilterAction {
@@ -163,8 +299,388 @@ pub struct BookmarkInfo {
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct PersistedStats {
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Legacy: history now lives in history.jsonl
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub history: Vec<HistoryEntry>,
     pub path_pick_counts: HashMap<String, u32>,
     #[serde(default)]
     pub recency_list_paths: Vec<String>,
+    /// When each path was first found by a crawl
+    #[serde(default)]
+    pub path_first_seen: HashMap<String, DateTime<Utc>>,
+}
+
+/// On-disk layout of bookmarks.json, the dedicated global bookmark store.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedBookmarks {
+    #[serde(default)]
+    pub schema_version: u32,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+/// One line of bookmarks.journal.jsonl: a change made since bookmarks.json
+/// was last written in full
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum BookmarkChange {
+    Upsert { bookmark: Bookmark },
+    Remove { hash: String },
+}
+
+/// Portable single-file export of a preset, including its local bookmarks and
+/// path weights. `roots` are the preset's folders at export time and are
+/// offered for remapping on import.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetBundle {
+    pub bundle_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub roots: Vec<String>,
+    pub preset: RandomiserPreset,
+}
+
+/// Rewrites every path under `from` to the same relative location under `to`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PathRemap {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleRoot {
+    pub path: String,
+    /// Whether the folder exists on this machine as-is
+    pub exists: bool,
+}
+
+/// What the import dialog shows before the user confirms the remaps
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetBundlePreview {
+    pub bundle: PresetBundle,
+    pub roots: Vec<BundleRoot>,
+    /// A preset with the same id already exists and will not be overwritten
+    pub id_conflict: bool,
+}
+
+/// Filters and paging for `query_history`. Every filter is optional; the
+/// date range is `from` inclusive to `to` exclusive.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistoryQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Folder the file must lie under, matched on whole path components
+    pub path_prefix: Option<String>,
+    /// Bookmark colour, case-insensitive. An empty string matches picks with
+    /// no colour.
+    pub color: Option<String>,
+    /// Case-insensitive substring of the file name or path
+    pub text: Option<String>,
+    pub offset: usize,
+    /// Defaults to 100 entries per page
+    pub limit: Option<usize>,
+    pub oldest_first: bool,
+}
+
+/// A history entry with its position in the full, chronological history
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryRow {
+    pub pick_number: usize,
+    #[serde(flatten)]
+    pub entry: HistoryEntry,
+    /// Bookmark colour at pick time, or the file's current colour for picks
+    /// recorded without diagnostics
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DayCount {
+    /// Local calendar date, YYYY-MM-DD
+    pub day: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderCount {
+    pub folder: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ColorCount {
+    pub color: Option<String>,
+    pub count: usize,
+}
+
+/// Counts over every entry matching the query, not just the returned page
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryAggregates {
+    pub per_day: Vec<DayCount>,
+    pub per_folder: Vec<FolderCount>,
+    pub per_color: Vec<ColorCount>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryPage {
+    pub entries: Vec<HistoryRow>,
+    /// Entries matching the query across all pages
+    pub total: usize,
+    pub offset: usize,
+    pub aggregates: HistoryAggregates,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum HistoryExportFormat {
+    Csv,
+    JsonLines,
+    Json,
+}
+
+/// Column sets matching the stats window's history.csv and diagnostics.csv
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum HistoryExportLayout {
+    History,
+    /// One row per pick that recorded diagnostics
+    Diagnostics,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryExportOptions {
+    pub format: HistoryExportFormat,
+    pub layout: HistoryExportLayout,
+    /// Overrides the layout's columns, in the given order
+    #[serde(default)]
+    pub columns: Option<Vec<String>>,
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+    /// Replace file names with stable "File 001" style labels
+    #[serde(default)]
+    pub anonymise: bool,
+    /// The labels the stats window shows, by file name, so anonymised exports
+    /// match it
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum HistoryImportMode {
+    /// Add imported entries to the existing history
+    Merge,
+    /// Discard the existing history, pick counts, recency list and undo stack
+    Replace,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryImportSummary {
+    /// Rows read from the file
+    pub rows: usize,
+    pub imported: usize,
+    /// Rows already in the history, matched on path and openedAt
+    pub duplicates: usize,
+    /// Rows with no usable name or openedAt, or no single file they could match
+    pub skipped: usize,
+    /// History length after the import
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CoveragePoint {
+    pub pick: usize,
+    pub unique: usize,
+    /// Expected unique files after this many uniform random picks
+    pub baseline: f64,
+}
+
+/// Unique files seen versus the birthday-problem baseline for uniform random
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageMetrics {
+    pub unique_files: usize,
+    /// Fraction of the pool seen, 0-1
+    pub coverage: f64,
+    pub baseline_unique: f64,
+    pub baseline_coverage: f64,
+    pub actual_repeats: usize,
+    pub expected_repeats: f64,
+    /// At most ~200 evenly spaced points
+    pub curve: Vec<CoveragePoint>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GapBucket {
+    /// Picks between two picks of the same file, inclusive bounds
+    pub min: usize,
+    /// None for the open-ended last bucket
+    pub max: Option<usize>,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RepeatGapMetrics {
+    pub histogram: Vec<GapBucket>,
+    pub mean_gap: Option<f64>,
+    /// Mean gap for uniform random picks, which is the pool size
+    pub expected_mean_gap: f64,
+}
+
+/// Observed pick counts against the counts the static weights predict
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DistributionFit {
+    pub chi_square: f64,
+    pub degrees_of_freedom: usize,
+    /// (chi_square - dof) / sqrt(2 * dof); beyond about ±2 is a poor fit
+    pub z_score: f64,
+    /// KL(observed || expected) in nats
+    pub kl_divergence: f64,
+    /// Picks of files whose expected weight is zero, left out of both tests
+    pub unexpected_picks: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StreakLengthCount {
+    pub length: usize,
+    pub count: usize,
+}
+
+/// Runs of consecutive picks sharing a folder or bookmark colour
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct StreakMetrics {
+    pub longest: usize,
+    pub mean_length: f64,
+    pub lengths: Vec<StreakLengthCount>,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RandomiserMetrics {
+    /// Picks of files in the current pool within the date range
+    pub picks: usize,
+    /// Picks of files no longer in the pool, left out of every metric
+    pub picks_outside_pool: usize,
+    pub pool_size: usize,
+    pub coverage: CoverageMetrics,
+    pub repeat_gaps: RepeatGapMetrics,
+    /// None until there is at least one pick and two weighted files
+    pub fit: Option<DistributionFit>,
+    pub folder_streaks: StreakMetrics,
+    pub color_streaks: StreakMetrics,
+}
+
+/// Path-keyed entries a preset would have rewritten by a relocation
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetRelocation {
+    pub id: String,
+    pub name: String,
+    pub paths: usize,
+    pub bookmarks: usize,
+    pub path_weights: usize,
+}
+
+/// How many entries of each kind of path-keyed state a relocation rewrites
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RelocationReport {
+    pub dry_run: bool,
+    pub from: String,
+    pub to: String,
+    pub pick_counts: usize,
+    /// Pick counts that landed on a path that already had one and were summed
+    pub pick_count_collisions: usize,
+    pub recency_entries: usize,
+    pub history_entries: usize,
+    pub global_bookmarks: usize,
+    /// Global path weights from settings
+    pub path_weights: usize,
+    pub preset_path_weights: usize,
+    pub saved_paths: usize,
+    pub files: usize,
+    pub first_seen_entries: usize,
+    /// Only presets with at least one rewritten entry
+    pub presets: Vec<PresetRelocation>,
+}
+
+/// How the picks in a session were made
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PickStrategy {
+    Weighted,
+    Series,
+    Blend,
+}
+
+/// A run of picks made under one preset, strategy and set of settings.
+/// Sessions are started explicitly, or by the first pick after an idle gap
+/// or a change to any of those.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PickSession {
+    pub id: String,
+    pub started_at: DateTime<Utc>,
+    /// None while the session is still open
+    pub ended_at: Option<DateTime<Utc>>,
+    pub last_pick_at: Option<DateTime<Utc>>,
+    /// Started by the user rather than by an idle gap or a change of setup
+    pub explicit: bool,
+    pub preset_id: Option<String>,
+    pub preset_name: Option<String>,
+    pub strategy: PickStrategy,
+    pub blend_id: Option<String>,
+    /// Randomiser settings at the start of the session
+    pub settings: FileRandomiserSettings,
+}
+
+/// On-disk layout of sessions.json, oldest session first
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedSessions {
+    #[serde(default)]
+    pub schema_version: u32,
+    pub sessions: Vec<PickSession>,
+}
+
+/// A session with statistics over the picks recorded in it
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    pub session: PickSession,
+    pub picks: usize,
+    pub unique_files: usize,
+    /// Picks of a file already picked earlier in the session
+    pub repeats: usize,
+    /// Picks of a file with a coloured bookmark
+    pub bookmarked_picks: usize,
+    pub mean_candidates: f64,
+    /// Mean of each pick's chosen weight over the mean candidate weight.
+    /// Uniform picking averages 1; higher means heavier files won more often.
+    pub mean_relative_weight: f64,
+    pub longest_folder_streak: usize,
+    pub constraint_violations: u32,
+    /// Start to end, or to the last pick while the session is open
+    pub duration_seconds: i64,
 }
@@ -1,3 +1,4 @@
+use crate::migrations::SETTINGS_SCHEMA_VERSION;
 use crate::models::Bookmark;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -9,7 +10,7 @@ pub struct ColorWeightEntry {
     pub global: f64,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(default, rename_all = "camelCase")]
 pub struct BookmarkPreference {
     pub enabled: bool,
@@ -18,11 +19,227 @@ pub struct BookmarkPreference {
     pub colors: HashMap<String, ColorWeightEntry>,
 }
 
-impl Default for BookmarkPreference {
+/// A hard rule on the picks within one session, enforced before sampling
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum PickConstraint {
+    /// At most `max` picks from the same folder per session
+    #[serde(rename_all = "camelCase")]
+    MaxPerFolder { max: u32 },
+    /// At least one pick bookmarked `color` in every `every` picks
+    #[serde(rename_all = "camelCase")]
+    ColorEvery { color: String, every: u32 },
+    /// Never the same file extension twice in a row
+    NoRepeatExtension,
+}
+
+impl PickConstraint {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            PickConstraint::MaxPerFolder { max } if *max == 0 => {
+                Err("Picks per folder must be at least 1".into())
+            }
+            PickConstraint::ColorEvery { color, .. } if color.trim().is_empty() => {
+                Err("A colour constraint needs a colour".into())
+            }
+            PickConstraint::ColorEvery { every, .. } if *every == 0 => {
+                Err("A colour constraint needs a window of at least 1 pick".into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// How the randomiser chooses the next file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PickMode {
+    /// Weighted random sampling over every candidate
+    #[default]
+    Weighted,
+    /// Choose a folder, then open its next unseen file in natural order
+    Series,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SeriesSettings {
+    /// Chance of continuing the most recently watched unfinished folder
+    /// rather than choosing a folder at random
+    pub continue_probability: f64,
+}
+
+impl Default for SeriesSettings {
+    fn default() -> Self {
+        Self {
+            continue_probability: 0.75,
+        }
+    }
+}
+
+/// The constants behind the randomiser's weighting. `profile` names the
+/// preset the values came from, or is "custom" once any of them is changed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AlgorithmTuning {
+    pub profile: String,
+    /// Recency window = sqrt(candidates) × scale, clamped to min..=max
+    pub recency_window_scale: f64,
+    pub recency_window_min: u32,
+    pub recency_window_max: u32,
+    /// Width of the order-bias bell curve as a fraction of the candidates
+    pub order_sigma: f64,
+    /// Weight added to the next-in-order file at randomness 0
+    pub order_boost: f64,
+    /// Factor for sharing a colour with the last pick / one of the last three
+    pub color_streak_last: f64,
+    pub color_streak_recent: f64,
+    /// Factor for sharing a folder with the last pick / one of the last three
+    pub folder_streak_last: f64,
+    pub folder_streak_recent: f64,
+    /// Upper bound on how many recent picks are blocked outright
+    pub hard_block_max: u32,
+}
+
+pub const CUSTOM_TUNING_PROFILE: &str = "custom";
+
+impl AlgorithmTuning {
+    pub fn balanced() -> Self {
+        Self {
+            profile: "balanced".into(),
+            recency_window_scale: 4.0,
+            recency_window_min: 15,
+            recency_window_max: 200,
+            order_sigma: 0.03,
+            order_boost: 10.0,
+            color_streak_last: 0.4,
+            color_streak_recent: 0.7,
+            folder_streak_last: 0.5,
+            folder_streak_recent: 0.8,
+            hard_block_max: 20,
+        }
+    }
+
+    /// Shorter memory and softer streak suppression
+    pub fn gentle() -> Self {
+        Self {
+            profile: "gentle".into(),
+            recency_window_scale: 3.0,
+            recency_window_min: 10,
+            recency_window_max: 120,
+            color_streak_last: 0.6,
+            color_streak_recent: 0.85,
+            folder_streak_last: 0.7,
+            folder_streak_recent: 0.9,
+            hard_block_max: 10,
+            ..Self::balanced()
+        }
+    }
+
+    /// Longer memory and stronger streak suppression
+    pub fn strict() -> Self {
+        Self {
+            profile: "strict".into(),
+            recency_window_scale: 6.0,
+            recency_window_min: 25,
+            recency_window_max: 400,
+            color_streak_last: 0.25,
+            color_streak_recent: 0.5,
+            folder_streak_last: 0.3,
+            folder_streak_recent: 0.6,
+            hard_block_max: 40,
+            ..Self::balanced()
+        }
+    }
+
+    pub fn profiles() -> Vec<Self> {
+        vec![Self::balanced(), Self::gentle(), Self::strict()]
+    }
+
+    /// How many recent picks the memory penalty covers for `len` candidates
+    pub fn recency_window(&self, len: usize) -> usize {
+        ((len as f64).sqrt() * self.recency_window_scale).clamp(
+            self.recency_window_min as f64,
+            self.recency_window_max as f64,
+        ) as usize
+    }
+
+    /// Check every value is in range and relabel as "custom" when the values
+    /// no longer match the named profile
+    pub fn validate(&mut self) -> Result<(), String> {
+        let positive = |name: &str, v: f64| {
+            if v > 0.0 && v.is_finite() {
+                Ok(())
+            } else {
+                Err(format!("{} must be greater than 0", name))
+            }
+        };
+        let factor = |name: &str, v: f64| {
+            if v > 0.0 && v <= 1.0 {
+                Ok(())
+            } else {
+                Err(format!("{} must be between 0 and 1", name))
+            }
+        };
+        positive("Recency window scale", self.recency_window_scale)?;
+        if self.recency_window_min == 0 || self.recency_window_min > self.recency_window_max {
+            return Err("Recency window minimum must be between 1 and the maximum".into());
+        }
+        positive("Order sigma", self.order_sigma)?;
+        if !(self.order_boost >= 0.0 && self.order_boost.is_finite()) {
+            return Err("Order boost must not be negative".into());
+        }
+        factor("Colour streak factor (last pick)", self.color_streak_last)?;
+        factor(
+            "Colour streak factor (recent picks)",
+            self.color_streak_recent,
+        )?;
+        factor("Folder streak factor (last pick)", self.folder_streak_last)?;
+        factor(
+            "Folder streak factor (recent picks)",
+            self.folder_streak_recent,
+        )?;
+
+        if self.profile != CUSTOM_TUNING_PROFILE {
+            let named = Self::profiles()
+                .into_iter()
+                .find(|p| p.profile == self.profile)
+                .ok_or_else(|| format!("Unknown tuning profile \"{}\"", self.profile))?;
+            if named != *self {
+                self.profile = CUSTOM_TUNING_PROFILE.into();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for AlgorithmTuning {
+    fn default() -> Self {
+        Self::balanced()
+    }
+}
+
+/// Extra weight for files that first turned up in a crawl recently, falling
+/// back towards 1 with a half-life
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct NewArrivalsBoost {
+    pub enabled: bool,
+    /// Weight multiplier for a file that has only just arrived
+    pub boost: f64,
+    /// Days for the boost to fall halfway back to 1
+    pub half_life_days: u32,
+    /// Also count a recently modified file as a new arrival
+    pub use_modified: bool,
+}
+
+impl Default for NewArrivalsBoost {
     fn default() -> Self {
         Self {
             enabled: false,
-            colors: HashMap::new(),
+            boost: 3.0,
+            half_life_days: 7,
+            use_modified: false,
         }
     }
 }
@@ -39,13 +256,31 @@ pub struct BookmarkColorOption {
 pub struct FileRandomiserSettings {
     pub allow_process_tracking: bool,
     pub randomness_level: u8,
+    pub pick_mode: PickMode,
+    pub series: SeriesSettings,
+    /// Legacy location of global bookmarks. Migrated into bookmarks.json on
+    /// startup and never written back.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub global_bookmarks: Vec<Bookmark>,
     pub bookmark_preference: BookmarkPreference,
     pub path_weights: HashMap<String, f64>,
     pub path_weights_enabled: bool,
     /// History retention window in days. 0 = keep forever.
     pub history_retention_days: u32,
+    /// Age in days at which a pick counts half as much towards the coverage
+    /// factor. 0 = picks never decay.
+    pub pick_count_half_life_days: u32,
+    pub new_arrivals: NewArrivalsBoost,
+    pub tuning: AlgorithmTuning,
+    /// Hard per-session rules for weighted picks; series mode ignores them
+    pub pick_constraints: Vec<PickConstraint>,
+    /// Minutes without a pick after which the next pick starts a new
+    /// session. 0 = sessions only end explicitly or on a change of setup.
+    pub session_idle_minutes: u32,
     pub persist_recency: bool,
+    /// User-defined roots usable as `${NAME}` in preset paths and path-weight
+    /// keys. Values may themselves use the built-in variables.
+    pub path_variables: HashMap<String, String>,
 }
 
 impl Default for FileRandomiserSettings {
@@ -53,30 +288,33 @@ impl Default for FileRandomiserSettings {
         FileRandomiserSettings {
             allow_process_tracking: false,
             randomness_level: 50,
+            pick_mode: PickMode::default(),
+            series: SeriesSettings::default(),
             global_bookmarks: vec![],
             bookmark_preference: BookmarkPreference::default(),
             path_weights: HashMap::new(),
             path_weights_enabled: false,
             history_retention_days: 180,
+            pick_count_half_life_days: 0,
+            new_arrivals: NewArrivalsBoost::default(),
+            tuning: AlgorithmTuning::default(),
+            pick_constraints: vec![],
+            session_idle_minutes: 30,
             persist_recency: false,
+            path_variables: HashMap::new(),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub enum DarkModeOption {
     Light,
     Dark,
+    #[default]
     System,
 }
 
-impl Default for DarkModeOption {
-    fn default() -> Self {
-        DarkModeOption::System
-    }
-}
-
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum LanguageOption {
@@ -132,7 +370,82 @@ impl Default for FileAuditorKeybinds {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// What opening a picked or audited file does
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum OpenAction {
+    /// Hand the file to the OS default application
+    #[default]
+    Default,
+    /// Open the file with a specific application
+    #[serde(rename_all = "camelCase")]
+    Application { path: String },
+    /// Run a shell command; `{path}` and `{dir}` are replaced with the
+    /// quoted file path and its folder
+    #[serde(rename_all = "camelCase")]
+    Command { template: String },
+    /// Select the file in the system file manager
+    Reveal,
+    /// Copy the file path to the clipboard
+    CopyPath,
+    /// Open the folder containing the file
+    OpenFolder,
+}
+
+impl OpenAction {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            OpenAction::Application { path } if path.trim().is_empty() => {
+                Err("An application open action needs an application".into())
+            }
+            OpenAction::Command { template } if template.trim().is_empty() => {
+                Err("A command open action needs a command".into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// An open action for files with one of `extensions`, optionally only while
+/// a preset is active. The first matching rule wins.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenActionRule {
+    /// Lowercase, without the leading dot. Empty matches every file.
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[serde(default)]
+    pub preset_id: Option<String>,
+    pub action: OpenAction,
+}
+
+impl OpenActionRule {
+    /// Normalise the extensions and check the action
+    pub fn validate(&mut self) -> Result<(), String> {
+        self.extensions = self
+            .extensions
+            .iter()
+            .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+            .filter(|e| !e.is_empty())
+            .collect();
+        self.action.validate()
+    }
+
+    pub fn matches(&self, path: &str, preset_id: Option<&str>) -> bool {
+        if self.preset_id.is_some() && self.preset_id.as_deref() != preset_id {
+            return false;
+        }
+        if self.extensions.is_empty() {
+            return true;
+        }
+        std::path::Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| self.extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", default)]
 pub struct FileAuditorSettings {
     pub allow_process_tracking: bool,
@@ -140,30 +453,23 @@ pub struct FileAuditorSettings {
     pub keybinds: FileAuditorKeybinds,
 }
 
-impl Default for FileAuditorSettings {
-    fn default() -> Self {
-        Self {
-            allow_process_tracking: false,
-            global_close_viewer_shortcut: false,
-            keybinds: Default::default(),
-        }
-    }
-}
-
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase", default)]
 pub struct AppSettings {
+    pub schema_version: u32,
     pub dark_mode: DarkModeOption,
     pub language: LanguageOption,
     pub custom_background: Option<String>,
     pub file_randomiser: FileRandomiserSettings,
     pub file_auditor: FileAuditorSettings,
     pub bookmark_colors: Vec<BookmarkColorOption>,
+    pub open_actions: Vec<OpenActionRule>,
 }
 
 impl Default for AppSettings {
     fn default() -> Self {
         AppSettings {
+            schema_version: SETTINGS_SCHEMA_VERSION,
             dark_mode: Default::default(),
             language: Default::default(),
             custom_background: None,
@@ -195,6 +501,7 @@ impl Default for AppSettings {
                     label: "Orange".to_string(),
                 },
             ],
+            open_actions: vec![],
         }
     }
 }
@@ -0,0 +1,426 @@
+use crate::models::settings::{AppSettings, OpenAction};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tauri_plugin_opener::OpenerExt;
+
+/// The action of the first rule matching `path` under `preset_id`, or the
+/// OS default when none does
+pub fn resolve_open_action(
+    settings: &AppSettings,
+    path: &str,
+    preset_id: Option<&str>,
+) -> OpenAction {
+    settings
+        .open_actions
+        .iter()
+        .find(|rule| rule.matches(path, preset_id))
+        .map(|rule| rule.action.clone())
+        .unwrap_or_default()
+}
+
+fn parent_dir(path: &str) -> String {
+    Path::new(path)
+        .parent()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Windows paths cannot contain double quotes, and cmd takes everything
+/// between them literally except `%` and, with delayed expansion, `!`. Those
+/// are stepped outside the quotes and escaped with `^`, e.g. `"50"^%" off"`,
+/// so the text between two of them is never a variable cmd could expand.
+/// Carets inside the quotes are already literal.
+#[cfg(target_os = "windows")]
+fn shell_quote(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '%' | '!' => {
+                quoted.push_str("\"^");
+                quoted.push(c);
+                quoted.push('"');
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(not(target_os = "windows"))]
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Fill `{path}` and `{dir}` in a command template with quoted values
+fn expand_template(template: &str, path: &str) -> String {
+    template
+        .replace("{path}", &shell_quote(path))
+        .replace("{dir}", &shell_quote(&parent_dir(path)))
+}
+
+#[cfg(target_os = "windows")]
+fn shell_command(command: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+    let mut cmd = Command::new("cmd");
+    // Passed raw so cmd sees the quotes exactly as written
+    cmd.arg("/C").raw_arg(command);
+    cmd
+}
+
+#[cfg(not(target_os = "windows"))]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command]);
+    cmd
+}
+
+#[cfg(target_os = "macos")]
+fn application_command(app_path: &str, path: &str, wait: bool) -> Command {
+    if app_path.ends_with(".app") {
+        let mut cmd = Command::new("open");
+        if wait {
+            cmd.arg("-W");
+        }
+        cmd.args(["-a", app_path, path]);
+        cmd
+    } else {
+        let mut cmd = Command::new(app_path);
+        cmd.arg(path);
+        cmd
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn application_command(app_path: &str, path: &str, _wait: bool) -> Command {
+    let mut cmd = Command::new(app_path);
+    cmd.arg(path);
+    cmd
+}
+
+/// Run `cmd` to completion when `wait` is set, otherwise detach it
+fn launch(mut cmd: Command, wait: bool) -> Result<bool, String> {
+    if wait {
+        cmd.status().map_err(|e| e.to_string())?;
+    } else {
+        cmd.spawn().map_err(|e| e.to_string())?;
+    }
+    Ok(wait)
+}
+
+fn pipe_to(program: &str, args: &[&str], input: &[u8]) -> std::io::Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input)?;
+    }
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!(
+            "{} exited with {}",
+            program, status
+        )))
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    // clip reads the console code page unless given UTF-16 with a BOM
+    let mut input = vec![0xFF, 0xFE];
+    input.extend(text.encode_utf16().flat_map(|u| u.to_le_bytes()));
+    pipe_to("clip", &[], &input).map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    pipe_to("pbcopy", &[], text.as_bytes()).map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    let tools: [(&str, &[&str]); 3] = [
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard"]),
+        ("xsel", &["--clipboard", "--input"]),
+    ];
+    for (program, args) in tools {
+        if pipe_to(program, args, text.as_bytes()).is_ok() {
+            return Ok(());
+        }
+    }
+    Err("No clipboard tool found (install wl-clipboard, xclip or xsel)".into())
+}
+
+#[cfg(target_os = "linux")]
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !text.is_empty()).then_some(text)
+}
+
+/// Where a desktop file ID can live: `$XDG_DATA_HOME` and `$XDG_DATA_DIRS`,
+/// with dashes in the ID standing for subdirectories
+#[cfg(target_os = "linux")]
+fn find_desktop_file(id: &str) -> Option<std::path::PathBuf> {
+    let data_home = std::env::var("XDG_DATA_HOME")
+        .ok()
+        .filter(|d| !d.is_empty())
+        .or_else(|| {
+            std::env::var("HOME")
+                .ok()
+                .map(|h| format!("{}/.local/share", h))
+        });
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    let mut names = vec![id.to_string()];
+    let mut name = id.to_string();
+    while let Some(dash) = name.find('-') {
+        name.replace_range(dash..=dash, "/");
+        names.push(name.clone());
+    }
+
+    data_home
+        .into_iter()
+        .chain(data_dirs.split(':').map(str::to_string))
+        .map(|dir| Path::new(&dir).join("applications"))
+        .flat_map(|dir| names.iter().map(move |n| dir.join(n)))
+        .find(|candidate| candidate.is_file())
+}
+
+/// A key from the `[Desktop Entry]` group, with value escapes resolved
+#[cfg(target_os = "linux")]
+fn desktop_entry_value(contents: &str, key: &str) -> Option<String> {
+    let mut in_entry = false;
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        let Some((k, v)) = line.split_once('=') else {
+            continue;
+        };
+        if in_entry && k.trim() == key {
+            let mut value = String::new();
+            let mut chars = v.trim().chars();
+            while let Some(c) = chars.next() {
+                if c != '\\' {
+                    value.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    Some('s') => value.push(' '),
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some('r') => value.push('\r'),
+                    Some(other) => value.push(other),
+                    None => {}
+                }
+            }
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Split an `Exec` value into arguments. Arguments may be double-quoted,
+/// with backslash escaping the next character inside quotes.
+#[cfg(target_os = "linux")]
+fn split_exec(exec: &str) -> Vec<String> {
+    let mut args = vec![];
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quoted = false;
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_arg = true;
+            }
+            '\\' if quoted => current.extend(chars.next()),
+            c if c.is_whitespace() && !quoted => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    args
+}
+
+/// Substitute the file into an `Exec` command line. File and URL codes
+/// become the path, the icon, name and location codes are dropped, and the
+/// path is appended when the line has no file code at all.
+#[cfg(target_os = "linux")]
+fn expand_exec(exec: &str, path: &str) -> Vec<String> {
+    let mut used_path = false;
+    let mut args = vec![];
+    for arg in split_exec(exec) {
+        if matches!(arg.as_str(), "%i" | "%c" | "%k") {
+            continue;
+        }
+        let mut expanded = String::new();
+        let mut chars = arg.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                expanded.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('%') => expanded.push('%'),
+                Some('f' | 'F' | 'u' | 'U') => {
+                    expanded.push_str(path);
+                    used_path = true;
+                }
+                _ => {}
+            }
+        }
+        args.push(expanded);
+    }
+    if !used_path {
+        args.push(path.to_string());
+    }
+    args
+}
+
+/// The default application for `path` per the MIME database, as a command
+/// that runs it in the foreground. None when `xdg-mime` is missing, no
+/// handler is registered, or the handler needs a terminal.
+#[cfg(target_os = "linux")]
+pub fn default_handler_command(path: &str) -> Option<Command> {
+    let mime = command_output("xdg-mime", &["query", "filetype", path])?;
+    let desktop_id = command_output("xdg-mime", &["query", "default", &mime])?;
+    let desktop_id = desktop_id.split(';').next()?.trim().to_string();
+    let contents = std::fs::read_to_string(find_desktop_file(&desktop_id)?).ok()?;
+
+    if desktop_entry_value(&contents, "Terminal").as_deref() == Some("true") {
+        return None;
+    }
+    let args = expand_exec(&desktop_entry_value(&contents, "Exec")?, path);
+    let (program, rest) = args.split_first()?;
+    let mut cmd = Command::new(program);
+    cmd.args(rest);
+    Some(cmd)
+}
+
+/// Carry out a non-default open action on `path`. When `wait` is set,
+/// actions that launch a program block until it exits. Returns whether it
+/// waited, so callers know whether a close can be reported.
+pub fn run_open_action(
+    app: &tauri::AppHandle,
+    action: &OpenAction,
+    path: &str,
+    wait: bool,
+) -> Result<bool, String> {
+    match action {
+        OpenAction::Default => app
+            .opener()
+            .open_path(path, None::<String>)
+            .map(|_| false)
+            .map_err(|e| e.to_string()),
+        OpenAction::Application { path: app_path } => {
+            launch(application_command(app_path, path, wait), wait)
+        }
+        OpenAction::Command { template } => {
+            launch(shell_command(&expand_template(template, path)), wait)
+        }
+        OpenAction::Reveal => {
+            crate::filesortercommands::reveal_in_explorer(path.to_string()).map(|_| false)
+        }
+        OpenAction::CopyPath => copy_to_clipboard(path).map(|_| false),
+        OpenAction::OpenFolder => app
+            .opener()
+            .open_path(parent_dir(path), None::<String>)
+            .map(|_| false)
+            .map_err(|e| e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn expand_template_single_quotes_values() {
+        assert_eq!(
+            expand_template("viewer {path} --dir {dir}", "/art/it's here.png"),
+            r"viewer '/art/it'\''s here.png' --dir '/art'"
+        );
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn expand_template_escapes_cmd_expansion() {
+        assert_eq!(
+            expand_template("viewer {path}", r"C:\Art\100% ^done!.png"),
+            r#"viewer "C:\Art\100"^%" ^done"^!".png""#
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn desktop_entry_value_reads_only_the_main_group() {
+        let contents = "\
+[Desktop Entry]
+Name=Viewer
+Exec=viewer\\s--fast %f
+Terminal = false
+
+[Desktop Action new]
+Exec=viewer --new
+";
+        assert_eq!(
+            desktop_entry_value(contents, "Exec").as_deref(),
+            Some("viewer --fast %f")
+        );
+        assert_eq!(
+            desktop_entry_value(contents, "Terminal").as_deref(),
+            Some("false")
+        );
+        assert_eq!(desktop_entry_value(contents, "Icon"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn split_exec_handles_quoted_arguments() {
+        assert_eq!(
+            split_exec(r#"  "/opt/My App/run" --title "say \"hi\"" "" %U "#),
+            ["/opt/My App/run", "--title", r#"say "hi""#, "", "%U"]
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn expand_exec_substitutes_the_file() {
+        let path = "/art/cat 1.png";
+        assert_eq!(
+            expand_exec("viewer %i %f --done 100%%", path),
+            ["viewer", path, "--done", "100%"]
+        );
+        assert_eq!(
+            expand_exec("viewer --file=%u", path),
+            ["viewer", "--file=/art/cat 1.png"]
+        );
+        assert_eq!(
+            expand_exec("viewer %c --new", path),
+            ["viewer", "--new", path]
+        );
+    }
+}
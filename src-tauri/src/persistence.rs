@@ -0,0 +1,253 @@
+use crate::migrations::{load_migrated, migrate_value, SchemaKind};
+use serde::de::DeserializeOwned;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Rotating backups kept beside each persisted file; `<name>.bak.1` is the newest
+pub const BACKUP_COUNT: usize = 3;
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}{}", name, suffix))
+}
+
+pub fn backup_paths(path: &Path) -> Vec<PathBuf> {
+    (1..=BACKUP_COUNT)
+        .map(|n| with_suffix(path, &format!(".bak.{}", n)))
+        .collect()
+}
+
+/// Replace `path` so it always holds either the old or the new contents in
+/// full: write a temp file beside it, fsync, then rename it into place.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let tmp = with_suffix(path, ".tmp");
+    {
+        let mut file = File::create(&tmp).map_err(|e| e.to_string())?;
+        file.write_all(contents).map_err(|e| e.to_string())?;
+        file.sync_all().map_err(|e| e.to_string())?;
+    }
+    if let Err(e) = fs::rename(&tmp, path) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e.to_string());
+    }
+
+    // Make the rename itself durable
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().and_then(|p| File::open(p).ok()) {
+        let _ = dir.sync_all();
+    }
+
+    Ok(())
+}
+
+/// Shift `<name>.bak.1..N` along by one and copy the current file to `.bak.1`
+fn rotate_backups(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let backups = backup_paths(path);
+    for n in (1..backups.len()).rev() {
+        if backups[n - 1].exists() {
+            fs::rename(&backups[n - 1], &backups[n]).map_err(|e| e.to_string())?;
+        }
+    }
+    fs::copy(path, &backups[0]).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Back up the current file, then replace it atomically
+pub fn save_with_backups(path: &Path, contents: &[u8]) -> Result<(), String> {
+    rotate_backups(path)?;
+    write_atomic(path, contents)
+}
+
+/// Move a damaged file aside as `<name>.corrupt-<timestamp>` so a later save
+/// can't rotate it out of the backups
+pub fn quarantine(path: &Path) -> Result<PathBuf, String> {
+    let target = with_suffix(
+        path,
+        &format!(".corrupt-{}", chrono::Utc::now().format("%Y%m%d%H%M%S")),
+    );
+    fs::rename(path, &target).map_err(|e| e.to_string())?;
+    Ok(target)
+}
+
+/// Load a persisted file, migrating it if needed. When the file is missing or
+/// fails to load, the newest backup that does load is restored in its place
+/// and the damaged file quarantined. Returns Ok(None) when neither the file
+/// nor any backup exists, and the original error when nothing could be read.
+pub fn load_with_recovery<T: DeserializeOwned>(
+    path: &Path,
+    kind: SchemaKind,
+) -> Result<Option<T>, String> {
+    let error = if path.exists() {
+        match load_migrated(path, kind) {
+            Ok(value) => return Ok(Some(value)),
+            Err(e) => Some(e),
+        }
+    } else {
+        None
+    };
+
+    for backup in backup_paths(path) {
+        if !backup.exists() {
+            continue;
+        }
+        // Check the backup before touching the damaged file. It may predate
+        // the current schema, so judge it as it will be after migrating.
+        let parses = fs::read_to_string(&backup)
+            .ok()
+            .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+            .and_then(|mut value| migrate_value(kind, &mut value).ok().map(|_| value))
+            .and_then(|value| serde_json::from_value::<T>(value).ok())
+            .is_some();
+        if !parses {
+            continue;
+        }
+
+        if path.exists() {
+            let moved = quarantine(path)?;
+            eprintln!("Moved unreadable {:?} to {:?}", path, moved);
+        }
+        fs::copy(&backup, path).map_err(|e| e.to_string())?;
+        eprintln!("Recovered {:?} from {:?}", path, backup);
+        return load_migrated(path, kind).map(Some);
+    }
+
+    match error {
+        Some(e) => Err(e),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Sample {
+        name: String,
+    }
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "persistence-test-{}",
+            crate::models::common::random_id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn sample(name: &str) -> String {
+        format!(r#"{{"schemaVersion":1,"name":"{}"}}"#, name)
+    }
+
+    fn load(path: &Path) -> Result<Option<Sample>, String> {
+        load_with_recovery(path, SchemaKind::Settings)
+    }
+
+    #[test]
+    fn write_atomic_replaces_contents_without_leaving_temp_file() {
+        let dir = temp_dir();
+        let path = dir.join("nested").join("data.json");
+        write_atomic(&path, b"one").unwrap();
+        write_atomic(&path, b"two").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "two");
+        assert!(!with_suffix(&path, ".tmp").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn save_with_backups_rotates_newest_first() {
+        let dir = temp_dir();
+        let path = dir.join("data.json");
+        for n in 1..=5 {
+            save_with_backups(&path, n.to_string().as_bytes()).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "5");
+        let backups: Vec<String> = backup_paths(&path)
+            .iter()
+            .map(|b| fs::read_to_string(b).unwrap())
+            .collect();
+        assert_eq!(backups, ["4", "3", "2"]);
+        assert!(!with_suffix(&path, &format!(".bak.{}", BACKUP_COUNT + 1)).exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn load_with_recovery_reads_a_good_file() {
+        let dir = temp_dir();
+        let path = dir.join("data.json");
+        assert_eq!(load(&path), Ok(None));
+        fs::write(&path, sample("good")).unwrap();
+        assert_eq!(load(&path).unwrap().unwrap().name, "good");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn load_with_recovery_restores_newest_valid_backup() {
+        let dir = temp_dir();
+        let path = dir.join("data.json");
+        let backups = backup_paths(&path);
+        fs::write(&path, "{ truncated").unwrap();
+        fs::write(&backups[0], "also broken").unwrap();
+        fs::write(&backups[1], sample("older")).unwrap();
+
+        assert_eq!(load(&path).unwrap().unwrap().name, "older");
+        assert_eq!(fs::read_to_string(&path).unwrap(), sample("older"));
+        let quarantined = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().contains(".corrupt-"))
+            .count();
+        assert_eq!(quarantined, 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn load_with_recovery_restores_a_missing_file_from_backup() {
+        let dir = temp_dir();
+        let path = dir.join("data.json");
+        fs::write(&backup_paths(&path)[2], sample("backup")).unwrap();
+        assert_eq!(load(&path).unwrap().unwrap().name, "backup");
+        assert!(path.exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn load_with_recovery_accepts_a_backup_that_needs_migrating() {
+        #[derive(Debug, Deserialize)]
+        struct Stats {
+            recency_list_paths: Vec<String>,
+        }
+
+        let dir = temp_dir();
+        let path = dir.join("stats.json");
+        fs::write(&path, "{ truncated").unwrap();
+        // Unversioned stats only gain recency_list_paths when migrated
+        fs::write(&backup_paths(&path)[0], "{}").unwrap();
+        let stats: Stats = load_with_recovery(&path, SchemaKind::Stats)
+            .unwrap()
+            .unwrap();
+        assert!(stats.recency_list_paths.is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn load_with_recovery_keeps_the_file_when_nothing_loads() {
+        let dir = temp_dir();
+        let path = dir.join("data.json");
+        fs::write(&path, "{ truncated").unwrap();
+        assert!(load(&path).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{ truncated");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,373 @@
+use crate::filerandomisercommands::resolve_path_variables;
+use crate::historycommands::path_key;
+use crate::migrations::{migrate_value, SchemaKind, PRESET_SCHEMA_VERSION};
+use crate::models::common::{random_id, remap_path_prefix};
+use crate::models::{
+    BundleRoot, InvalidPresetFile, PathRemap, PresetBundle, PresetBundlePreview, PresetListing,
+    RandomiserPreset,
+};
+use crate::persistence::{backup_paths, load_with_recovery, save_with_backups};
+use crate::setting_commands::get_app_settings;
+use chrono::Utc;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+use tauri_plugin_dialog::{DialogExt, FilePath};
+use tauri_plugin_opener::OpenerExt;
+
+const BUNDLE_VERSION: u32 = 1;
+
+pub(crate) fn presets_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("presets");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// Preset files are keyed on id, so two presets never share a file
+pub(crate) fn preset_file_path(dir: &Path, id: &str) -> PathBuf {
+    let safe_id = id.replace(|c: char| !c.is_alphanumeric() && c != '-' && c != '_', "_");
+    dir.join(format!("{}.json", safe_id))
+}
+
+/// Read every preset in the presets folder. Files from before presets were
+/// keyed on id are renamed to `<id>.json` on the way through, files from
+/// older schema versions are migrated, and damaged files are restored from
+/// their newest valid backup.
+pub(crate) fn load_presets(app: &tauri::AppHandle) -> Result<PresetListing, String> {
+    let dir = presets_dir(app)?;
+    let mut listing = PresetListing::default();
+
+    for entry in std::fs::read_dir(&dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+
+        // Only process .json files
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let preset = match load_with_recovery::<RandomiserPreset>(&path, SchemaKind::Preset) {
+            Ok(Some(preset)) => preset,
+            Ok(None) => continue,
+            Err(error) => {
+                listing.invalid.push(InvalidPresetFile { file_name, error });
+                continue;
+            }
+        };
+
+        let id_path = preset_file_path(&dir, &preset.id);
+        if path != id_path {
+            if id_path.exists() {
+                listing.invalid.push(InvalidPresetFile {
+                    file_name,
+                    error: format!("Duplicate of preset id {}", preset.id),
+                });
+                continue;
+            }
+            let _ = std::fs::rename(&path, &id_path);
+        }
+        listing.presets.push(preset);
+    }
+
+    listing.presets.sort_by_key(|p| p.name.to_lowercase());
+    Ok(listing)
+}
+
+/// On-disk contents of a preset file, stamped with the current schema version
+pub(crate) fn preset_json(preset: &RandomiserPreset) -> Result<String, String> {
+    let preset = RandomiserPreset {
+        schema_version: PRESET_SCHEMA_VERSION,
+        ..preset.clone()
+    };
+    serde_json::to_string_pretty(&preset).map_err(|e| e.to_string())
+}
+
+fn write_preset(app: &tauri::AppHandle, preset: &RandomiserPreset) -> Result<(), String> {
+    let file_path = preset_file_path(&presets_dir(app)?, &preset.id);
+    save_with_backups(&file_path, preset_json(preset)?.as_bytes())
+}
+
+fn find_preset(app: &tauri::AppHandle, id: &str) -> Result<RandomiserPreset, String> {
+    load_presets(app)?
+        .presets
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("Preset not found: {}", id))
+}
+
+#[tauri::command]
+pub fn open_presets_folder(app: tauri::AppHandle) -> Result<(), String> {
+    let presets_dir = presets_dir(&app)?;
+
+    app.opener()
+        .open_path(presets_dir.to_string_lossy(), None::<String>)
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_presets(app: tauri::AppHandle) -> Result<PresetListing, String> {
+    load_presets(&app)
+}
+
+#[tauri::command]
+pub fn save_preset(app: tauri::AppHandle, preset: RandomiserPreset) -> Result<(), String> {
+    if preset.id.trim().is_empty() {
+        return Err("Preset id must not be empty".into());
+    }
+    write_preset(&app, &preset)
+}
+
+#[tauri::command]
+pub fn rename_preset(
+    app: tauri::AppHandle,
+    id: String,
+    name: String,
+) -> Result<RandomiserPreset, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Preset name must not be empty".into());
+    }
+    let mut preset = find_preset(&app, &id)?;
+    preset.name = name.to_string();
+    write_preset(&app, &preset)?;
+    Ok(preset)
+}
+
+/// Move a preset file to the OS trash and drop its backups
+#[tauri::command]
+pub fn delete_preset(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    let file_path = preset_file_path(&presets_dir(&app)?, &id);
+    if !file_path.exists() {
+        return Err(format!("Preset not found: {}", id));
+    }
+    trash::delete(&file_path).map_err(|e| e.to_string())?;
+    for backup in backup_paths(&file_path) {
+        let _ = std::fs::remove_file(backup);
+    }
+    Ok(())
+}
+
+/// Copy a preset under a new id. Defaults the name to "<name> (copy)".
+#[tauri::command]
+pub fn duplicate_preset(
+    app: tauri::AppHandle,
+    id: String,
+    name: Option<String>,
+) -> Result<RandomiserPreset, String> {
+    let mut preset = find_preset(&app, &id)?;
+    preset.id = random_id();
+    preset.name = name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| format!("{} (copy)", preset.name));
+    write_preset(&app, &preset)?;
+    Ok(preset)
+}
+
+/// Apply the remap with the longest matching `from`, or keep the path as-is
+fn remap_path(path: &str, remaps: &[PathRemap]) -> String {
+    remaps
+        .iter()
+        .filter_map(|r| remap_path_prefix(path, &r.from, &r.to).map(|p| (r.from.len(), p)))
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, p)| p)
+        .unwrap_or_else(|| path.to_string())
+}
+
+fn remap_preset(preset: &mut RandomiserPreset, remaps: &[PathRemap]) {
+    for saved in preset.paths.iter_mut() {
+        if let FilePath::Path(p) = &saved.path {
+            let remapped = remap_path(&p.to_string_lossy(), remaps);
+            saved.path = FilePath::Path(remapped.into());
+        }
+    }
+    for bm in preset.bookmarks.iter_mut() {
+        if let FilePath::Path(p) = &bm.path {
+            let remapped = remap_path(&p.to_string_lossy(), remaps);
+            bm.path = FilePath::Path(remapped.into());
+        }
+    }
+    preset.path_weights = preset
+        .path_weights
+        .drain()
+        .map(|(k, v)| (remap_path(&k, remaps), v))
+        .collect();
+}
+
+/// Write a saved preset, with its bookmarks and path weights, to one bundle file
+#[tauri::command]
+pub async fn export_preset_bundle(
+    app: tauri::AppHandle,
+    preset_id: String,
+) -> Result<bool, String> {
+    let preset = load_presets(&app)?
+        .presets
+        .into_iter()
+        .find(|p| p.id == preset_id)
+        .ok_or_else(|| format!("Preset not found: {}", preset_id))?;
+
+    let bundle = PresetBundle {
+        bundle_version: BUNDLE_VERSION,
+        exported_at: Utc::now(),
+        roots: preset.paths.iter().map(|p| path_key(&p.path)).collect(),
+        preset,
+    };
+    let json = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+
+    let safe_name = bundle
+        .preset
+        .name
+        .replace(|c: char| !c.is_alphanumeric() && c != '-' && c != '_', "_");
+    // Dialog and write on a blocking thread
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = app
+            .dialog()
+            .file()
+            .add_filter("Preset bundle", &["json"])
+            .set_file_name(format!("{}.preset.json", safe_name))
+            .blocking_save_file();
+        match path {
+            Some(FilePath::Path(p)) => std::fs::write(p, json)
+                .map(|_| true)
+                .map_err(|e| e.to_string()),
+            Some(FilePath::Url(_)) => Err("URL paths not supported for preset export".to_string()),
+            None => Ok(false), // user cancelled
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Check a bundle's version and bring its preset up to the current schema
+fn parse_bundle(mut value: Value) -> Result<PresetBundle, String> {
+    let bundle_version = value
+        .get("bundleVersion")
+        .and_then(Value::as_u64)
+        .ok_or("Invalid preset bundle: missing bundleVersion")?;
+    if bundle_version > BUNDLE_VERSION as u64 {
+        return Err(format!(
+            "Preset bundle version {} is newer than this app supports",
+            bundle_version
+        ));
+    }
+    let preset = value
+        .get_mut("preset")
+        .ok_or("Invalid preset bundle: missing preset")?;
+    migrate_value(SchemaKind::Preset, preset)?;
+    serde_json::from_value(value).map_err(|e| format!("Invalid preset bundle: {}", e))
+}
+
+/// Read a bundle and report which of its root folders exist on this machine,
+/// so the user can choose replacements before importing
+#[tauri::command]
+pub async fn open_preset_bundle(
+    app: tauri::AppHandle,
+) -> Result<Option<PresetBundlePreview>, String> {
+    // Dialog and read on a blocking thread
+    let app_for_dialog = app.clone();
+    let content = tauri::async_runtime::spawn_blocking(move || {
+        let picked = app_for_dialog
+            .dialog()
+            .file()
+            .add_filter("Preset bundle", &["json"])
+            .blocking_pick_file();
+        match picked {
+            Some(FilePath::Path(p)) => std::fs::read_to_string(p)
+                .map(Some)
+                .map_err(|e| e.to_string()),
+            Some(FilePath::Url(_)) => Err("URL paths not supported for preset import".to_string()),
+            None => Ok(None), // user cancelled
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    let Some(content) = content else {
+        return Ok(None);
+    };
+
+    let bundle = parse_bundle(
+        serde_json::from_str(&content).map_err(|e| format!("Invalid preset bundle: {}", e))?,
+    )?;
+
+    let settings = get_app_settings(app.clone())?;
+    let path_variables = resolve_path_variables(&app, &settings);
+    let roots = bundle
+        .roots
+        .iter()
+        .map(|r| BundleRoot {
+            path: r.clone(),
+            exists: std::path::Path::new(&path_variables.expand(r)).is_dir(),
+        })
+        .collect();
+    let id_conflict = load_presets(&app)?
+        .presets
+        .iter()
+        .any(|p| p.id == bundle.preset.id);
+
+    Ok(Some(PresetBundlePreview {
+        bundle,
+        roots,
+        id_conflict,
+    }))
+}
+
+/// Save the bundled preset with its root folders remapped. A preset that
+/// would replace an existing one gets a fresh id instead.
+#[tauri::command]
+pub fn import_preset_bundle(
+    app: tauri::AppHandle,
+    bundle: Value,
+    remaps: Vec<PathRemap>,
+) -> Result<RandomiserPreset, String> {
+    let mut preset = parse_bundle(bundle)?.preset;
+    let remaps: Vec<PathRemap> = remaps
+        .into_iter()
+        .filter(|r| !r.from.is_empty() && !r.to.is_empty() && r.from != r.to)
+        .collect();
+    remap_preset(&mut preset, &remaps);
+
+    if load_presets(&app)?
+        .presets
+        .iter()
+        .any(|p| p.id == preset.id)
+    {
+        preset.id = random_id();
+    }
+
+    write_preset(&app, &preset)?;
+    Ok(preset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_bundle_migrates_the_preset() {
+        let bundle = parse_bundle(json!({
+            "bundleVersion": 1,
+            "exportedAt": "2024-01-01T00:00:00Z",
+            "roots": [],
+            "preset": { "id": "p1", "name": "Art", "paths": [], "filterRules": [], "shuffle": null },
+        }))
+        .unwrap();
+        assert_eq!(bundle.preset.schema_version, PRESET_SCHEMA_VERSION);
+        assert!(bundle.preset.bookmarks.is_empty());
+    }
+
+    #[test]
+    fn parse_bundle_rejects_newer_versions() {
+        let err = parse_bundle(json!({ "bundleVersion": BUNDLE_VERSION + 1 })).unwrap_err();
+        assert!(err.contains("newer"));
+    }
+}
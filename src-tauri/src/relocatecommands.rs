@@ -0,0 +1,324 @@
+use crate::blendcommands::LoadedBlend;
+use crate::bookmarkcommands::{
+    bookmark_journal_path, bookmarks_file_path, bookmarks_json, BookmarkJournal, GlobalBookmarks,
+};
+use crate::filerandomisercommands::{
+    load_persisted_stats, stats_file_path, PathFirstSeen, PathPickCounts,
+};
+use crate::historylog::{history_log_contents, history_log_path};
+use crate::migrations::STATS_SCHEMA_VERSION;
+use crate::models::common::remap_path_prefix;
+use crate::models::{
+    AppStateData, BookmarkIndex, PersistedStats, PresetRelocation, RelocationReport,
+};
+use crate::persistence::{save_with_backups, write_atomic};
+use crate::presetcommands::{load_presets, preset_file_path, preset_json, presets_dir};
+use crate::setting_commands::{get_app_settings, set_app_settings};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{Manager, State};
+use tauri_plugin_dialog::FilePath;
+
+/// Rewrite `path` in place if it lies under `from`. Returns whether it changed.
+fn relocate_path(path: &mut FilePath, from: &str, to: &str) -> bool {
+    if let FilePath::Path(p) = path {
+        if let Some(relocated) = remap_path_prefix(&p.to_string_lossy(), from, to) {
+            *path = FilePath::Path(relocated.into());
+            return true;
+        }
+    }
+    false
+}
+
+/// Rewrite the keys of a path-keyed map, returning the new map and how many
+/// keys moved. A moved key that lands on an existing one replaces it.
+fn relocate_keys<V: Clone>(
+    map: &HashMap<String, V>,
+    from: &str,
+    to: &str,
+) -> (HashMap<String, V>, usize) {
+    let mut moved = 0;
+    let mut relocated = HashMap::with_capacity(map.len());
+    let mut moved_entries = Vec::new();
+    for (key, value) in map {
+        match remap_path_prefix(key, from, to) {
+            Some(new_key) => {
+                moved += 1;
+                moved_entries.push((new_key, value.clone()));
+            }
+            None => {
+                relocated.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    relocated.extend(moved_entries);
+    (relocated, moved)
+}
+
+/// A file write staged for a relocation, with the contents to restore if a
+/// later write fails
+struct StagedWrite {
+    path: PathBuf,
+    contents: Vec<u8>,
+    original: Option<Vec<u8>>,
+}
+
+impl StagedWrite {
+    fn new(path: PathBuf, contents: String) -> Self {
+        let original = std::fs::read(&path).ok();
+        StagedWrite {
+            path,
+            contents: contents.into_bytes(),
+            original,
+        }
+    }
+}
+
+fn rollback(applied: &[StagedWrite]) {
+    for write in applied.iter().rev() {
+        let restored = match &write.original {
+            Some(original) => write_atomic(&write.path, original),
+            None => std::fs::remove_file(&write.path).map_err(|e| e.to_string()),
+        };
+        if let Err(e) = restored {
+            eprintln!("Failed to roll back {:?}: {}", write.path, e);
+        }
+    }
+}
+
+/// Write every staged file, restoring the ones already written if any fails
+fn apply_writes(writes: &[StagedWrite]) -> Result<(), String> {
+    for (i, write) in writes.iter().enumerate() {
+        if let Err(e) = save_with_backups(&write.path, &write.contents) {
+            rollback(&writes[..i]);
+            return Err(format!("Failed to write {:?}: {}", write.path, e));
+        }
+    }
+    Ok(())
+}
+
+/// Move everything keyed by absolute path from the `from` folder to `to`:
+/// pick counts, first-seen dates, the persisted recency list, history, global
+/// and preset bookmarks, path weights, saved paths and the crawled files. With
+/// `dry_run` nothing is changed and the report shows what would be.
+/// Files are written together and rolled back as a group if one fails.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn relocate_library(
+    app: tauri::AppHandle,
+    app_data: State<'_, Mutex<AppStateData>>,
+    path_pick_counts: State<'_, PathPickCounts>,
+    path_first_seen: State<'_, PathFirstSeen>,
+    global_store: State<'_, GlobalBookmarks>,
+    loaded_blend: State<'_, LoadedBlend>,
+    from: String,
+    to: String,
+    dry_run: bool,
+) -> Result<RelocationReport, String> {
+    let from = from.trim().to_string();
+    let to = to.trim().to_string();
+    if from.is_empty() || to.is_empty() {
+        return Err("Both the old and new folder are required".into());
+    }
+    if from == to {
+        return Err("The old and new folder are the same".into());
+    }
+
+    let mut settings = get_app_settings(app.clone())?;
+    let presets = load_presets(&app)?.presets;
+    let stats = load_persisted_stats(&app).unwrap_or_default();
+
+    // Same lock order as crawl_paths
+    let mut blend_session = loaded_blend.0.lock().unwrap();
+    let mut data = app_data.lock().unwrap();
+    let mut global_bookmarks = global_store.0.lock().unwrap();
+    let mut counts = path_pick_counts.0.lock().unwrap();
+    let mut first_seen = path_first_seen.0.lock().unwrap();
+
+    let mut report = RelocationReport {
+        dry_run,
+        from: from.clone(),
+        to: to.clone(),
+        ..Default::default()
+    };
+
+    // Pick counts that collide are summed rather than replaced
+    let mut new_counts: HashMap<String, u32> = HashMap::with_capacity(counts.len());
+    let mut moved_counts = Vec::new();
+    for (path, count) in counts.iter() {
+        match remap_path_prefix(path, &from, &to) {
+            Some(new_path) => moved_counts.push((new_path, *count)),
+            None => {
+                new_counts.insert(path.clone(), *count);
+            }
+        }
+    }
+    report.pick_counts = moved_counts.len();
+    for (path, count) in moved_counts {
+        let total = new_counts.entry(path).or_insert(0);
+        if *total > 0 {
+            report.pick_count_collisions += 1;
+        }
+        *total += count;
+    }
+
+    let recency_list_paths: Vec<String> = stats
+        .recency_list_paths
+        .iter()
+        .map(|p| match remap_path_prefix(p, &from, &to) {
+            Some(new_path) => {
+                report.recency_entries += 1;
+                new_path
+            }
+            None => p.clone(),
+        })
+        .collect();
+
+    // Keep the earlier date when a moved path lands on a known one
+    let mut new_first_seen = HashMap::with_capacity(first_seen.len());
+    let mut moved_first_seen = Vec::new();
+    for (path, seen) in first_seen.iter() {
+        match remap_path_prefix(path, &from, &to) {
+            Some(new_path) => moved_first_seen.push((new_path, *seen)),
+            None => {
+                new_first_seen.insert(path.clone(), *seen);
+            }
+        }
+    }
+    report.first_seen_entries = moved_first_seen.len();
+    for (path, seen) in moved_first_seen {
+        let earliest = new_first_seen.entry(path).or_insert(seen);
+        *earliest = (*earliest).min(seen);
+    }
+
+    let mut history = data.history.clone();
+    report.history_entries = history
+        .iter_mut()
+        .map(|h| relocate_path(&mut h.path, &from, &to))
+        .filter(|moved| *moved)
+        .count();
+
+    let mut bookmarks = global_bookmarks.to_vec();
+    report.global_bookmarks = bookmarks
+        .iter_mut()
+        .map(|b| relocate_path(&mut b.path, &from, &to))
+        .filter(|moved| *moved)
+        .count();
+
+    let (path_weights, moved) = relocate_keys(&settings.file_randomiser.path_weights, &from, &to);
+    report.path_weights = moved;
+    let (preset_path_weights, moved) = relocate_keys(&data.preset_path_weights, &from, &to);
+    report.preset_path_weights = moved;
+
+    let mut saved_paths = data.paths.clone();
+    report.saved_paths = saved_paths
+        .iter_mut()
+        .map(|p| relocate_path(&mut p.path, &from, &to))
+        .filter(|moved| *moved)
+        .count();
+    let mut files = data.files.clone();
+    report.files = files
+        .iter_mut()
+        .map(|f| relocate_path(&mut f.path, &from, &to))
+        .filter(|moved| *moved)
+        .count();
+
+    let mut changed_presets = Vec::new();
+    for mut preset in presets {
+        let paths = preset
+            .paths
+            .iter_mut()
+            .map(|p| relocate_path(&mut p.path, &from, &to))
+            .filter(|moved| *moved)
+            .count();
+        let bookmarks = preset
+            .bookmarks
+            .iter_mut()
+            .map(|b| relocate_path(&mut b.path, &from, &to))
+            .filter(|moved| *moved)
+            .count();
+        let (weights, path_weights) = relocate_keys(&preset.path_weights, &from, &to);
+        preset.path_weights = weights;
+        if paths + bookmarks + path_weights > 0 {
+            report.presets.push(PresetRelocation {
+                id: preset.id.clone(),
+                name: preset.name.clone(),
+                paths,
+                bookmarks,
+                path_weights,
+            });
+            changed_presets.push(preset);
+        }
+    }
+
+    if dry_run {
+        return Ok(report);
+    }
+
+    let mut writes = Vec::new();
+    let dir = presets_dir(&app)?;
+    for preset in &changed_presets {
+        writes.push(StagedWrite::new(
+            preset_file_path(&dir, &preset.id),
+            preset_json(preset)?,
+        ));
+    }
+    let bookmark_index = BookmarkIndex::from(bookmarks);
+    if report.global_bookmarks > 0 {
+        let path = bookmarks_file_path(&app).ok_or("App data directory unavailable")?;
+        writes.push(StagedWrite::new(path, bookmarks_json(&bookmark_index)?));
+        // The snapshot already holds every journalled change
+        let journal = bookmark_journal_path(&app).ok_or("App data directory unavailable")?;
+        if journal.exists() {
+            writes.push(StagedWrite::new(journal, String::new()));
+        }
+    }
+    if report.pick_counts > 0 || report.recency_entries > 0 || report.first_seen_entries > 0 {
+        let path = stats_file_path(&app).ok_or("App data directory unavailable")?;
+        let stats = PersistedStats {
+            schema_version: STATS_SCHEMA_VERSION,
+            history: vec![],
+            path_pick_counts: new_counts.clone(),
+            recency_list_paths,
+            path_first_seen: new_first_seen.clone(),
+        };
+        let json = serde_json::to_string_pretty(&stats).map_err(|e| e.to_string())?;
+        writes.push(StagedWrite::new(path, json));
+    }
+    if report.history_entries > 0 {
+        let path = history_log_path(&app).ok_or("App data directory unavailable")?;
+        writes.push(StagedWrite::new(path, history_log_contents(&history)?));
+    }
+    apply_writes(&writes)?;
+
+    if report.path_weights > 0 {
+        settings.file_randomiser.path_weights = path_weights;
+        if let Err(e) = set_app_settings(app.clone(), settings) {
+            rollback(&writes);
+            return Err(e);
+        }
+    }
+
+    // Everything is on disk; bring the in-memory state along
+    *counts = new_counts;
+    *first_seen = new_first_seen;
+    if report.global_bookmarks > 0 {
+        *app.state::<BookmarkJournal>().0.lock().unwrap() = 0;
+    }
+    *global_bookmarks = bookmark_index;
+    data.history = history;
+    data.paths = saved_paths;
+    data.files = files;
+    data.preset_path_weights = preset_path_weights;
+    if let Some(session) = blend_session.as_mut() {
+        for pool in session.pools.iter_mut() {
+            for file in pool.files.iter_mut() {
+                relocate_path(&mut file.path, &from, &to);
+            }
+            pool.path_weights = relocate_keys(&pool.path_weights, &from, &to).0;
+        }
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,239 @@
+use crate::historycommands::{parent_folder, path_key};
+use crate::models::common::natural_cmp;
+use crate::models::settings::SeriesSettings;
+use crate::models::{AppStateData, FileEntry, SeriesProgress};
+use chrono::{DateTime, Utc};
+use rand::prelude::*;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::State;
+
+/// One folder's candidates in natural order, and how far history has got
+struct SeriesFolder {
+    folder: String,
+    /// Candidate indices, sorted by file name
+    files: Vec<usize>,
+    /// Files opened in the current pass through the folder
+    seen: Vec<bool>,
+    /// Position in `files` of the most recently opened one
+    last_opened: Option<(DateTime<Utc>, usize)>,
+}
+
+impl SeriesFolder {
+    fn seen_count(&self) -> usize {
+        self.seen.iter().filter(|s| **s).count()
+    }
+
+    fn in_progress(&self) -> bool {
+        let seen = self.seen_count();
+        seen > 0 && seen < self.files.len()
+    }
+
+    /// Position of the first unseen file after the most recently opened one,
+    /// wrapping round to any earlier unseen file
+    fn next_unseen(&self) -> Option<usize> {
+        let start = self.last_opened.map(|(_, pos)| pos + 1).unwrap_or(0);
+        (start..self.files.len())
+            .chain(0..start)
+            .find(|&pos| !self.seen[pos])
+    }
+}
+
+/// Group candidates by parent folder and replay history over them. A file
+/// counts as seen once opened, whether it was picked or opened by hand, until
+/// the folder starts over: opening the first file after every file has been
+/// seen begins a new pass with only that file seen.
+fn series_folders(candidates: &[(usize, &FileEntry)], data: &AppStateData) -> Vec<SeriesFolder> {
+    let mut by_folder: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, (_, file)) in candidates.iter().enumerate() {
+        by_folder
+            .entry(parent_folder(&file.path))
+            .or_default()
+            .push(i);
+    }
+
+    let mut folders: Vec<SeriesFolder> = by_folder
+        .into_iter()
+        .map(|(folder, mut files)| {
+            files.sort_by(|a, b| natural_cmp(&candidates[*a].1.name, &candidates[*b].1.name));
+            SeriesFolder {
+                folder,
+                seen: vec![false; files.len()],
+                files,
+                last_opened: None,
+            }
+        })
+        .collect();
+    folders.sort_by(|a, b| natural_cmp(&a.folder, &b.folder));
+
+    // Path -> (folder index, position in the folder)
+    let mut positions: HashMap<String, (usize, usize)> = HashMap::new();
+    for (f, folder) in folders.iter().enumerate() {
+        for (pos, i) in folder.files.iter().enumerate() {
+            positions.insert(path_key(&candidates[*i].1.path), (f, pos));
+        }
+    }
+
+    let mut opens: Vec<(DateTime<Utc>, (usize, usize))> = data
+        .history
+        .iter()
+        .filter_map(|entry| Some((entry.opened_at, *positions.get(&path_key(&entry.path))?)))
+        .collect();
+    opens.sort_by_key(|(at, _)| *at);
+    for (at, (f, pos)) in opens {
+        let folder = &mut folders[f];
+        if pos == 0 && folder.seen.iter().all(|s| *s) {
+            folder.seen.fill(false);
+        }
+        folder.seen[pos] = true;
+        folder.last_opened = Some((at, pos));
+    }
+    folders
+}
+
+/// Choose the next series-mode file, returning its candidate index and
+/// folder. Usually continues the most recently watched unfinished folder;
+/// otherwise starts or resumes a random folder that still has unseen files in
+/// its current pass. Once every file has been seen, a random folder starts
+/// over from the top.
+pub(crate) fn choose_series_file(
+    candidates: &[(usize, &FileEntry)],
+    data: &AppStateData,
+    settings: &SeriesSettings,
+    rng: &mut impl Rng,
+) -> Option<(usize, String)> {
+    let folders = series_folders(candidates, data);
+
+    let current = folders
+        .iter()
+        .filter(|f| f.in_progress())
+        .max_by_key(|f| f.last_opened.map(|(at, _)| at));
+    let continue_probability = settings.continue_probability.clamp(0.0, 1.0);
+    let folder = match current {
+        Some(folder) if rng.random_bool(continue_probability) => folder,
+        _ => {
+            let unfinished: Vec<&SeriesFolder> = folders
+                .iter()
+                .filter(|f| f.next_unseen().is_some())
+                .collect();
+            match unfinished.choose(rng) {
+                Some(folder) => *folder,
+                None => {
+                    let folder = folders.choose(rng)?;
+                    return Some((*folder.files.first()?, folder.folder.clone()));
+                }
+            }
+        }
+    };
+
+    let pos = folder.next_unseen()?;
+    Some((folder.files[pos], folder.folder.clone()))
+}
+
+/// Per-folder series progress over the current candidates
+#[tauri::command]
+pub fn get_series_progress(app_data: State<'_, Mutex<AppStateData>>) -> Vec<SeriesProgress> {
+    let data = app_data.lock().unwrap();
+    let candidates: Vec<(usize, &FileEntry)> = data
+        .files
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| !f.excluded)
+        .collect();
+
+    series_folders(&candidates, &data)
+        .iter()
+        .map(|folder| SeriesProgress {
+            folder: folder.folder.clone(),
+            total: folder.files.len(),
+            seen: folder.seen_count(),
+            next: folder
+                .next_unseen()
+                .map(|pos| candidates[folder.files[pos]].1.name.clone()),
+            last_opened_at: folder.last_opened.map(|(at, _)| at),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::HistoryEntry;
+    use rand::rngs::StdRng;
+    use tauri_plugin_dialog::FilePath;
+
+    const EPISODES: [&str; 3] = ["/show/ep 1.mkv", "/show/ep 2.mkv", "/show/ep 10.mkv"];
+
+    fn file(id: u64, path: &str) -> FileEntry {
+        FileEntry {
+            id,
+            name: path.rsplit('/').next().unwrap().to_string(),
+            path: FilePath::Path(path.into()),
+            excluded: false,
+            hash: None,
+            bookmark: None,
+            first_seen: None,
+            modified: None,
+        }
+    }
+
+    /// Pick the next episode after opening `opened` (indices into EPISODES)
+    /// in order, one minute apart
+    fn next_after(opened: &[usize]) -> String {
+        let start = Utc::now();
+        let data = AppStateData {
+            history: opened
+                .iter()
+                .enumerate()
+                .map(|(minute, i)| HistoryEntry {
+                    id: *i as u64,
+                    name: String::new(),
+                    path: FilePath::Path(EPISODES[*i].into()),
+                    opened_at: start + chrono::Duration::minutes(minute as i64),
+                    diagnostics: None,
+                    session_id: None,
+                })
+                .collect(),
+            ..Default::default()
+        };
+        // Listed out of order to check the natural sort
+        let files = [
+            file(2, EPISODES[2]),
+            file(0, EPISODES[0]),
+            file(1, EPISODES[1]),
+        ];
+        let candidates: Vec<(usize, &FileEntry)> = files.iter().enumerate().collect();
+        let settings = SeriesSettings {
+            continue_probability: 1.0,
+        };
+        let mut rng = StdRng::seed_from_u64(7);
+        let (chosen, folder) = choose_series_file(&candidates, &data, &settings, &mut rng).unwrap();
+        assert_eq!(folder, "/show");
+        path_key(&candidates[chosen].1.path)
+    }
+
+    #[test]
+    fn starts_and_continues_in_natural_order() {
+        assert_eq!(next_after(&[]), EPISODES[0]);
+        assert_eq!(next_after(&[0]), EPISODES[1]);
+        assert_eq!(next_after(&[0, 1]), EPISODES[2]);
+    }
+
+    #[test]
+    fn wraps_round_to_earlier_unseen_files() {
+        assert_eq!(next_after(&[1, 2]), EPISODES[0]);
+    }
+
+    #[test]
+    fn restarts_after_every_file_is_seen_and_keeps_going() {
+        assert_eq!(next_after(&[0, 1, 2]), EPISODES[0]);
+        assert_eq!(next_after(&[0, 1, 2, 0]), EPISODES[1]);
+        assert_eq!(next_after(&[0, 1, 2, 0, 1]), EPISODES[2]);
+        assert_eq!(next_after(&[0, 1, 2, 0, 1, 2]), EPISODES[0]);
+    }
+
+    #[test]
+    fn reopening_the_first_file_mid_pass_does_not_restart() {
+        assert_eq!(next_after(&[0, 1, 0]), EPISODES[2]);
+    }
+}
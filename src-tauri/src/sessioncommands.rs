@@ -0,0 +1,409 @@
+use crate::blendcommands::LoadedBlend;
+use crate::filerandomisercommands::STATS_SAVE_DELAY;
+use crate::historycommands::{parent_folder, path_key};
+use crate::metricscommands::streak_metrics;
+use crate::migrations::{SchemaKind, SESSIONS_SCHEMA_VERSION};
+use crate::models::settings::{AppSettings, PickMode};
+use crate::models::{
+    AppStateData, HistoryEntry, PersistedSessions, PickSession, PickStrategy, SessionSummary,
+};
+use crate::persistence::{load_with_recovery, quarantine, save_with_backups};
+use crate::setting_commands::get_app_settings;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::Manager;
+use tauri::State;
+
+pub struct SessionState {
+    /// Oldest first. The last session is open while its `ended_at` is None.
+    pub sessions: Vec<PickSession>,
+    /// Preset the frontend last applied, as (id, name)
+    pub active_preset: Option<(String, String)>,
+}
+
+pub struct PickSessions(pub Mutex<SessionState>);
+
+impl SessionState {
+    pub fn new(sessions: Vec<PickSession>) -> Self {
+        SessionState {
+            sessions,
+            active_preset: None,
+        }
+    }
+
+    fn open_session(&self) -> Option<&PickSession> {
+        self.sessions.last().filter(|s| s.ended_at.is_none())
+    }
+
+    /// The open session, if a pick at `at` under this setup would continue it
+    /// rather than start a new one
+    pub(crate) fn continuing(
+        &self,
+        settings: &AppSettings,
+        blend_id: Option<&str>,
+        at: DateTime<Utc>,
+    ) -> Option<&PickSession> {
+        let session = self.open_session()?;
+        let idle_minutes = settings.file_randomiser.session_idle_minutes;
+        let last_active = session.last_pick_at.unwrap_or(session.started_at);
+        if idle_minutes > 0 && at - last_active > Duration::minutes(idle_minutes as i64) {
+            return None;
+        }
+        let preset = self.active_preset.as_ref().map(|(id, _)| id.as_str());
+        let same_setup = session.preset_id.as_deref() == preset
+            && session.strategy == strategy(settings, blend_id)
+            && session.blend_id.as_deref() == blend_id
+            && same_settings(session, settings);
+        same_setup.then_some(session)
+    }
+
+    /// Close the open session, if any, at `at`
+    fn end_open(&mut self, at: DateTime<Utc>) -> Option<&PickSession> {
+        let session = self.sessions.last_mut().filter(|s| s.ended_at.is_none())?;
+        session.ended_at = Some(at.max(session.started_at));
+        Some(session)
+    }
+
+    fn start(
+        &mut self,
+        settings: &AppSettings,
+        blend_id: Option<String>,
+        at: DateTime<Utc>,
+        explicit: bool,
+    ) -> &PickSession {
+        let (preset_id, preset_name) = self.active_preset.clone().unzip();
+        self.sessions.push(PickSession {
+            id: at.format("%Y%m%d-%H%M%S-%3f").to_string(),
+            started_at: at,
+            ended_at: None,
+            last_pick_at: None,
+            explicit,
+            preset_id,
+            preset_name,
+            strategy: strategy(settings, blend_id.as_deref()),
+            blend_id,
+            settings: settings.file_randomiser.clone(),
+        });
+        self.sessions.last().unwrap()
+    }
+
+    /// Drop closed sessions that ended before the history retention window
+    fn prune(&mut self, retention_days: u32, now: DateTime<Utc>) {
+        if retention_days == 0 {
+            return;
+        }
+        let cutoff = now - Duration::days(retention_days as i64);
+        self.sessions
+            .retain(|s| s.ended_at.is_none_or(|ended| ended >= cutoff));
+    }
+}
+
+fn strategy(settings: &AppSettings, blend_id: Option<&str>) -> PickStrategy {
+    match (blend_id, settings.file_randomiser.pick_mode) {
+        (Some(_), _) => PickStrategy::Blend,
+        (None, PickMode::Weighted) => PickStrategy::Weighted,
+        (None, PickMode::Series) => PickStrategy::Series,
+    }
+}
+
+/// Settings have no equality of their own, so compare them as JSON
+fn same_settings(session: &PickSession, settings: &AppSettings) -> bool {
+    match (
+        serde_json::to_value(&session.settings),
+        serde_json::to_value(&settings.file_randomiser),
+    ) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn sessions_file_path(app: &tauri::AppHandle) -> Option<std::path::PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(|d| d.join("sessions.json"))
+}
+
+/// Read sessions.json, recovering from backups like the stats file. Returns
+/// an empty list when there is no file yet or nothing could be read.
+pub fn load_sessions(app: &tauri::AppHandle) -> Vec<PickSession> {
+    let Some(path) = sessions_file_path(app) else {
+        return vec![];
+    };
+    match load_with_recovery::<PersistedSessions>(&path, SchemaKind::Sessions) {
+        Ok(persisted) => persisted.map(|p| p.sessions).unwrap_or_default(),
+        Err(e) => {
+            eprintln!("Failed to load pick sessions: {}", e);
+            if let Ok(moved) = quarantine(&path) {
+                eprintln!("Moved unreadable sessions to {:?}", moved);
+            }
+            vec![]
+        }
+    }
+}
+
+fn save_sessions(app: &tauri::AppHandle, state: &mut SessionState, settings: &AppSettings) {
+    let Some(path) = sessions_file_path(app) else {
+        return;
+    };
+    state.prune(settings.file_randomiser.history_retention_days, Utc::now());
+    let persisted = PersistedSessions {
+        schema_version: SESSIONS_SCHEMA_VERSION,
+        sessions: state.sessions.clone(),
+    };
+    let result = serde_json::to_vec_pretty(&persisted)
+        .map_err(|e| e.to_string())
+        .and_then(|json| save_with_backups(&path, &json));
+    if let Err(e) = result {
+        eprintln!("Failed to save pick sessions: {}", e);
+    }
+}
+
+/// Set while a deferred sessions save is waiting to run
+pub struct SessionsSavePending(pub AtomicBool);
+
+/// Save the sessions after `STATS_SAVE_DELAY`, unless a save is already
+/// waiting. Picks within a session only move `last_pick_at`, so they don't
+/// rewrite sessions.json each time; starting or ending a session saves at once.
+fn schedule_sessions_save(app: &tauri::AppHandle) {
+    let pending = app.state::<SessionsSavePending>();
+    if pending.0.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(STATS_SAVE_DELAY);
+        flush_sessions_save(&app);
+    });
+}
+
+/// Run a waiting deferred sessions save now. Called on exit so nothing is lost.
+pub fn flush_sessions_save(app: &tauri::AppHandle) {
+    let pending = app.state::<SessionsSavePending>();
+    if !pending.0.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let settings = match get_app_settings(app.clone()) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("Failed to save pick sessions: {}", e);
+            return;
+        }
+    };
+    let sessions = app.state::<PickSessions>();
+    let mut state = sessions.0.lock().unwrap();
+    save_sessions(app, &mut state, &settings);
+}
+
+/// What recording one pick changed in the sessions
+pub(crate) struct SessionUndo {
+    pub session_id: String,
+    previous_last_pick_at: Option<DateTime<Utc>>,
+    /// Set when the pick started a new session: the session it ended, as it
+    /// was before, or None if no session was open
+    started: Option<Option<PickSession>>,
+}
+
+/// Assign a pick made at `at` to a session, starting a new one when the
+/// open session has gone idle or the preset, strategy or settings changed.
+/// The returned session ID is stored on the history entry.
+pub(crate) fn record_pick(
+    app: &tauri::AppHandle,
+    settings: &AppSettings,
+    blend_id: Option<String>,
+    at: DateTime<Utc>,
+) -> SessionUndo {
+    let sessions = app.state::<PickSessions>();
+    let mut state = sessions.0.lock().unwrap();
+    let mut started = None;
+    if state
+        .continuing(settings, blend_id.as_deref(), at)
+        .is_none()
+    {
+        let previous = state.open_session().cloned();
+        // An automatic end falls at the last activity, not at this pick
+        if let Some(ended) = previous
+            .as_ref()
+            .map(|s| s.last_pick_at.unwrap_or(s.started_at))
+        {
+            state.end_open(ended);
+        }
+        state.start(settings, blend_id, at, false);
+        started = Some(previous);
+    }
+    let session = state.sessions.last_mut().unwrap();
+    let undo = SessionUndo {
+        session_id: session.id.clone(),
+        previous_last_pick_at: session.last_pick_at.replace(at),
+        started,
+    };
+    if undo.started.is_some() {
+        save_sessions(app, &mut state, settings);
+    } else {
+        schedule_sessions_save(app);
+    }
+    undo
+}
+
+/// Whether `session_id` is the open session
+pub(crate) fn is_open_session(app: &tauri::AppHandle, session_id: &str) -> bool {
+    let sessions = app.state::<PickSessions>();
+    let state = sessions.0.lock().unwrap();
+    state.open_session().is_some_and(|s| s.id == session_id)
+}
+
+/// ID of the preset the frontend last applied
+pub(crate) fn active_preset_id(app: &tauri::AppHandle) -> Option<String> {
+    let sessions = app.state::<PickSessions>();
+    let state = sessions.0.lock().unwrap();
+    state.active_preset.as_ref().map(|(id, _)| id.clone())
+}
+
+/// Take a pick back out of its session. A session the pick started is
+/// removed and the one it ended reopened.
+pub(crate) fn undo_pick(app: &tauri::AppHandle, settings: &AppSettings, undo: SessionUndo) {
+    let sessions = app.state::<PickSessions>();
+    let mut state = sessions.0.lock().unwrap();
+    let Some(pos) = state.sessions.iter().position(|s| s.id == undo.session_id) else {
+        return;
+    };
+    match undo.started {
+        Some(previous) if pos + 1 == state.sessions.len() => {
+            state.sessions.pop();
+            if let Some(previous) = previous {
+                if let Some(last) = state.sessions.last_mut().filter(|s| s.id == previous.id) {
+                    *last = previous;
+                }
+            }
+            save_sessions(app, &mut state, settings);
+        }
+        _ => {
+            state.sessions[pos].last_pick_at = undo.previous_last_pick_at;
+            schedule_sessions_save(app);
+        }
+    }
+}
+
+/// End any open session and start a new one under the current setup
+#[tauri::command]
+pub fn start_pick_session(
+    app: tauri::AppHandle,
+    loaded_blend: State<'_, LoadedBlend>,
+    sessions: State<'_, PickSessions>,
+) -> Result<PickSession, String> {
+    let settings = get_app_settings(app.clone())?;
+    let blend_id = loaded_blend
+        .0
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|b| b.blend.id.clone());
+
+    let mut state = sessions.0.lock().unwrap();
+    let now = Utc::now();
+    state.end_open(now);
+    let session = state.start(&settings, blend_id, now, true).clone();
+    save_sessions(&app, &mut state, &settings);
+    Ok(session)
+}
+
+/// End the open session. Returns it, or None if no session was open.
+#[tauri::command]
+pub fn end_pick_session(
+    app: tauri::AppHandle,
+    sessions: State<'_, PickSessions>,
+) -> Result<Option<PickSession>, String> {
+    let settings = get_app_settings(app.clone())?;
+    let mut state = sessions.0.lock().unwrap();
+    let Some(session) = state.end_open(Utc::now()).cloned() else {
+        return Ok(None);
+    };
+    save_sessions(&app, &mut state, &settings);
+    Ok(Some(session))
+}
+
+/// Record the preset the frontend applied, or None once it is cleared. The
+/// next pick starts a new session if the preset differs from the open one's.
+#[tauri::command]
+pub fn set_active_preset(
+    sessions: State<'_, PickSessions>,
+    id: Option<String>,
+    name: Option<String>,
+) {
+    sessions.0.lock().unwrap().active_preset = id.map(|id| (id, name.unwrap_or_default()));
+}
+
+fn summarise(session: &PickSession, picks: &[&HistoryEntry]) -> SessionSummary {
+    let mut seen = HashSet::new();
+    let repeats = picks
+        .iter()
+        .filter(|h| !seen.insert(path_key(&h.path)))
+        .count();
+    let diagnostics: Vec<_> = picks
+        .iter()
+        .filter_map(|h| h.diagnostics.as_ref())
+        .collect();
+    let mean = |values: Vec<f64>| {
+        if values.is_empty() {
+            0.0
+        } else {
+            values.iter().sum::<f64>() / values.len() as f64
+        }
+    };
+    let folders: Vec<String> = picks.iter().map(|h| parent_folder(&h.path)).collect();
+    let end = session
+        .ended_at
+        .or(session.last_pick_at)
+        .unwrap_or(session.started_at);
+
+    SessionSummary {
+        session: session.clone(),
+        picks: picks.len(),
+        unique_files: seen.len(),
+        repeats,
+        bookmarked_picks: diagnostics
+            .iter()
+            .filter(|d| d.chosen_bookmark_color.is_some())
+            .count(),
+        mean_candidates: mean(diagnostics.iter().map(|d| d.candidates as f64).collect()),
+        mean_relative_weight: mean(
+            diagnostics
+                .iter()
+                .filter(|d| d.weight_mean > 0.0)
+                .map(|d| d.chosen_weight / d.weight_mean)
+                .collect(),
+        ),
+        longest_folder_streak: streak_metrics(folders.iter().map(|f| Some(f.as_str()))).longest,
+        constraint_violations: diagnostics.iter().map(|d| d.constraint_violations).sum(),
+        duration_seconds: (end - session.started_at).num_seconds(),
+    }
+}
+
+/// Every retained session with statistics over its picks, newest first
+#[tauri::command]
+pub fn get_session_summaries(
+    app_data: State<'_, Mutex<AppStateData>>,
+    sessions: State<'_, PickSessions>,
+) -> Vec<SessionSummary> {
+    let data = app_data.lock().unwrap();
+    let state = sessions.0.lock().unwrap();
+
+    let mut picks: HashMap<&str, Vec<&HistoryEntry>> = HashMap::new();
+    for entry in &data.history {
+        if let Some(id) = &entry.session_id {
+            picks.entry(id.as_str()).or_default().push(entry);
+        }
+    }
+
+    state
+        .sessions
+        .iter()
+        .rev()
+        .map(|session| {
+            let session_picks = picks.get(session.id.as_str()).map(Vec::as_slice);
+            summarise(session, session_picks.unwrap_or_default())
+        })
+        .collect()
+}
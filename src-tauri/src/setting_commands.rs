@@ -1,5 +1,5 @@
-use crate::models::settings::{AppSettings, FileAuditorKeybinds};
-use crate::models::Bookmark;
+use crate::migrations::{backup_file, migrate_value, schema_version, SchemaKind};
+use crate::models::settings::{AlgorithmTuning, AppSettings, FileAuditorKeybinds};
 use crate::models::DarkModeOption;
 use crate::models::LanguageOption;
 use base64::{engine::general_purpose, Engine as _};
@@ -12,6 +12,41 @@ use tauri_plugin_dialog::FilePath;
 use tauri_plugin_opener::OpenerExt;
 use tauri_plugin_store::StoreExt;
 
+/// Upgrade the settings blob in store.json to the current schema. store.json
+/// is backed up first; if that fails the settings are left as they are.
+pub fn migrate_settings(app: &AppHandle<Wry>) {
+    let Ok(store) = app.store("store.json") else {
+        return;
+    };
+    let Some(mut value) = store.get("settings") else {
+        return;
+    };
+    let version = schema_version(SchemaKind::Settings, &value);
+    if version >= SchemaKind::Settings.current() {
+        return;
+    }
+
+    let backed_up = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())
+        .and_then(|dir| backup_file(&dir.join("store.json"), version));
+    if let Err(e) = backed_up {
+        eprintln!("Skipping settings migration, backup failed: {}", e);
+        return;
+    }
+
+    match migrate_value(SchemaKind::Settings, &mut value) {
+        Ok(_) => {
+            store.set("settings", value);
+            if let Err(e) = store.save() {
+                eprintln!("Failed to save migrated settings: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Failed to migrate settings: {}", e),
+    }
+}
+
 /// Get the current settings, merging persisted store + runtime context menu status
 #[tauri::command]
 pub fn get_app_settings(app: AppHandle<Wry>) -> Result<AppSettings, String> {
@@ -23,32 +58,56 @@ pub fn get_app_settings(app: AppHandle<Wry>) -> Result<AppSettings, String> {
         None => AppSettings::default(),
     };
 
-    // Force-disable process tracking on non-Windows
-    #[cfg(not(target_os = "windows"))]
+    // Force-disable process tracking where it is unsupported
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
     {
         settings.file_randomiser.allow_process_tracking = false;
     }
 
+    // Hand-edited tuning could otherwise break the weighting
+    if let Err(e) = settings.file_randomiser.tuning.validate() {
+        eprintln!("Invalid algorithm tuning, using defaults: {}", e);
+        settings.file_randomiser.tuning = AlgorithmTuning::default();
+    }
+
     Ok(settings)
 }
 
+/// The named algorithm tuning profiles
+#[tauri::command]
+pub fn get_tuning_profiles() -> Vec<AlgorithmTuning> {
+    AlgorithmTuning::profiles()
+}
+
 /// Save updated settings to the persistent store
 #[tauri::command]
 pub fn set_app_settings(
     app: AppHandle<Wry>,
     #[allow(unused_mut)] mut settings: AppSettings,
 ) -> Result<AppSettings, String> {
-    #[cfg(not(target_os = "windows"))]
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
     {
         settings.file_randomiser.allow_process_tracking = false;
     }
+    settings.schema_version = SchemaKind::Settings.current();
+    settings.file_randomiser.tuning.validate()?;
+    for constraint in &settings.file_randomiser.pick_constraints {
+        constraint.validate()?;
+    }
+    for rule in &mut settings.open_actions {
+        rule.validate()?;
+    }
 
     // Preserve bookmark_colors from the store — never let a frontend
-    // call overwrite manual JSON edits
+    // call overwrite manual JSON edits. Legacy global bookmarks are only
+    // ever removed by the bookmarks.json migration.
     let store = app.store("store.json").map_err(|e| e.to_string())?;
+    settings.file_randomiser.global_bookmarks.clear();
     if let Some(existing) = store.get("settings") {
         if let Ok(existing_settings) = serde_json::from_value::<AppSettings>(existing) {
             settings.bookmark_colors = existing_settings.bookmark_colors;
+            settings.file_randomiser.global_bookmarks =
+                existing_settings.file_randomiser.global_bookmarks;
         }
     }
 
@@ -64,17 +123,7 @@ pub fn toggle_auditor_process_tracking(
     enable: bool,
 ) -> Result<AppSettings, String> {
     let mut settings = get_app_settings(app.clone())?;
-
-    #[cfg(target_os = "windows")]
-    {
-        settings.file_auditor.allow_process_tracking = enable;
-    }
-
-    #[cfg(not(target_os = "windows"))]
-    {
-        settings.file_auditor.allow_process_tracking = false;
-    }
-
+    settings.file_auditor.allow_process_tracking = enable && cfg!(target_os = "windows");
     set_app_settings(app, settings.clone())?;
     Ok(settings)
 }
@@ -84,12 +133,12 @@ pub fn toggle_auditor_process_tracking(
 pub fn toggle_process_tracking(app: AppHandle<Wry>, enable: bool) -> Result<AppSettings, String> {
     let mut settings = get_app_settings(app.clone())?;
 
-    #[cfg(target_os = "windows")]
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
     {
         settings.file_randomiser.allow_process_tracking = enable;
     }
 
-    #[cfg(not(target_os = "windows"))]
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
     {
         settings.file_randomiser.allow_process_tracking = false;
     }
@@ -179,23 +228,6 @@ pub fn restart_app(app_handle: AppHandle) {
     app_handle.restart();
 }
 
-#[tauri::command]
-pub fn get_global_bookmarks(app: AppHandle<Wry>) -> Result<Vec<Bookmark>, String> {
-    let settings = get_app_settings(app)?;
-    Ok(settings.file_randomiser.global_bookmarks)
-}
-
-#[tauri::command]
-pub fn set_global_bookmarks(
-    app: AppHandle<Wry>,
-    bookmarks: Vec<Bookmark>,
-) -> Result<Vec<Bookmark>, String> {
-    let mut settings = get_app_settings(app.clone())?;
-    settings.file_randomiser.global_bookmarks = bookmarks.clone();
-    set_app_settings(app, settings)?;
-    Ok(bookmarks)
-}
-
 #[tauri::command]
 pub fn set_file_auditor_keybinds(
     app: AppHandle<Wry>,
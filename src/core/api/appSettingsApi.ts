@@ -1,5 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
 import {
+  AlgorithmTuning,
   AppSettings,
   DarkModeOption,
   FileAuditorKeybinds,
@@ -12,12 +13,25 @@ export const getAppSettings = () => invoke<AppSettings>("get_app_settings");
 export const setAppSettings = (settings: AppSettings) =>
   invoke<void>("set_app_settings", { settings });
 
+export const getTuningProfiles = () =>
+  invoke<AlgorithmTuning[]>("get_tuning_profiles");
+
 export const getGlobalBookmarks = () =>
   invoke<Bookmark[]>("get_global_bookmarks");
 
 export const setGlobalBookmarks = (bookmarks: Bookmark[]) =>
   invoke<void>("set_global_bookmarks", { bookmarks });
 
+/** Resolves the stored bookmarks */
+export const upsertGlobalBookmarks = (bookmarks: Bookmark[]) =>
+  invoke<Bookmark[]>("upsert_global_bookmarks", { bookmarks });
+
+export const removeGlobalBookmarks = (hashes: string[]) =>
+  invoke<number>("remove_global_bookmarks", { hashes });
+
+export const updateGlobalBookmark = (hash: string, color: string | null) =>
+  invoke<Bookmark>("update_global_bookmark", { hash, color });
+
 export const toggleProcessTracking = (enable: boolean) =>
   invoke<AppSettings>("toggle_process_tracking", { enable });
 
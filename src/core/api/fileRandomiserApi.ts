@@ -1,5 +1,21 @@
 import { invoke } from "@tauri-apps/api/core";
-import { AppStateData, FileScore } from "../../types/filerandomiser";
+import {
+  AppStateData,
+  FileEntry,
+  FileScore,
+  HistoryEntry,
+  HistoryExportOptions,
+  HistoryImportMode,
+  HistoryImportSummary,
+  HistoryPage,
+  HistoryQuery,
+  PickExplanation,
+  PickSession,
+  RandomiserMetrics,
+  RelocationReport,
+  SeriesProgress,
+  SessionSummary,
+} from "../../types/filerandomiser";
 import { Bookmark } from "../../types/common";
 
 export const getAppState = () => invoke<AppStateData>("get_app_state");
@@ -12,15 +28,21 @@ export const addPathViaDialog = () => invoke("add_path_via_dialog");
 export const removePath = (id: number) =>
   invoke<boolean>("remove_path", { id });
 
-export const crawlPaths = (
-  globalBookmarks: Bookmark[],
-  localBookmarks: Bookmark[],
-) => invoke("crawl_paths", { globalBookmarks, localBookmarks });
+/** Resolves the new file list, or null if the crawl was cancelled */
+export const crawlPaths = (localBookmarks: Bookmark[]) =>
+  invoke<FileEntry[] | null>("crawl_paths", { localBookmarks });
+
+/** Resolves whether a crawl was running */
+export const cancelCrawl = () => invoke<boolean>("cancel_crawl");
 
 export const openFileById = (id: number) => invoke("open_file_by_id", { id });
 
 export const pickRandomFile = () => invoke("pick_random_file");
 
+/** Resolves the removed history entry, or null if there was nothing to undo */
+export const undoLastPick = () =>
+  invoke<HistoryEntry | null>("undo_last_pick");
+
 export const openPath = (path: string) => invoke("open_path", { path });
 
 export const setPresetPathWeights = (weights: Record<string, number>) =>
@@ -34,11 +56,52 @@ export const updateFileBookmark = (
 
 export const getFileScores = () => invoke<FileScore[]>("get_file_scores");
 
+export const explainPick = (id: number) =>
+  invoke<PickExplanation>("explain_pick", { id });
+
+export const getSeriesProgress = () =>
+  invoke<SeriesProgress[]>("get_series_progress");
+
 export const updateFileBookmarksBulk = (
   hashes: string[],
   color: string | null,
   isGlobal: boolean,
 ) => invoke<void>("update_file_bookmarks_bulk", { hashes, color, isGlobal });
 
+export const getPathVariables = () =>
+  invoke<Record<string, string>>("get_path_variables");
+
+export const queryHistory = (query: HistoryQuery) =>
+  invoke<HistoryPage>("query_history", { query });
+
+/** Resolves false if the user cancelled the save dialog */
+export const exportHistory = (options: HistoryExportOptions) =>
+  invoke<boolean>("export_history", { options });
+
+/** Resolves null if the user cancelled the file dialog */
+export const importHistory = (mode: HistoryImportMode) =>
+  invoke<HistoryImportSummary | null>("import_history", { mode });
+
+export const getRandomiserMetrics = (from?: string, to?: string) =>
+  invoke<RandomiserMetrics>("get_randomiser_metrics", { from, to });
+
+export const startPickSession = () =>
+  invoke<PickSession>("start_pick_session");
+
+/** Resolves null if no session was open */
+export const endPickSession = () =>
+  invoke<PickSession | null>("end_pick_session");
+
+/** Pass null once the preset is cleared */
+export const setActivePreset = (id: string | null, name: string | null) =>
+  invoke<void>("set_active_preset", { id, name });
+
+/** Newest first */
+export const getSessionSummaries = () =>
+  invoke<SessionSummary[]>("get_session_summaries");
+
+export const relocateLibrary = (from: string, to: string, dryRun: boolean) =>
+  invoke<RelocationReport>("relocate_library", { from, to, dryRun });
+
 export const saveCsv = (filename: string, content: string) =>
   invoke<void>("save_csv", { filename, content });
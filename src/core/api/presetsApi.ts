@@ -1,11 +1,50 @@
 import { invoke } from "@tauri-apps/api/core";
-import { RandomiserPreset } from "../../types/filerandomiser";
+import {
+  BlendListing,
+  BlendPick,
+  FileEntry,
+  PathRemap,
+  PresetBlend,
+  PresetBundle,
+  PresetBundlePreview,
+  PresetListing,
+  RandomiserPreset,
+} from "../../types/filerandomiser";
 
-export const getPresets = async (): Promise<RandomiserPreset[]> =>
+export const getPresets = async (): Promise<PresetListing> =>
   invoke("get_presets");
 
 export const savePreset = async (preset: RandomiserPreset) =>
   invoke("save_preset", { preset });
 
+export const renamePreset = (id: string, name: string) =>
+  invoke<RandomiserPreset>("rename_preset", { id, name });
+
+export const deletePreset = (id: string) =>
+  invoke<void>("delete_preset", { id });
+
+export const duplicatePreset = (id: string, name?: string) =>
+  invoke<RandomiserPreset>("duplicate_preset", { id, name });
+
 export const openPresetsFolder = (): Promise<void> =>
   invoke("open_presets_folder");
+
+export const exportPresetBundle = (presetId: string) =>
+  invoke<boolean>("export_preset_bundle", { presetId });
+
+export const openPresetBundle = () =>
+  invoke<PresetBundlePreview | null>("open_preset_bundle");
+
+export const importPresetBundle = (bundle: PresetBundle, remaps: PathRemap[]) =>
+  invoke<RandomiserPreset>("import_preset_bundle", { bundle, remaps });
+
+export const getBlends = () => invoke<BlendListing>("get_blends");
+
+export const saveBlend = (blend: PresetBlend) =>
+  invoke<void>("save_blend", { blend });
+
+export const loadBlend = (blendId: string) =>
+  invoke<FileEntry[] | null>("load_blend", { blendId });
+
+export const pickBlendedFile = () =>
+  invoke<BlendPick | null>("pick_blended_file");
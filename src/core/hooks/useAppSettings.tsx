@@ -2,8 +2,10 @@ import { useCallback, useEffect, useState } from "react";
 import {
   getAppSettings,
   getGlobalBookmarks,
+  removeGlobalBookmarks,
   setAppSettings,
   setGlobalBookmarks,
+  upsertGlobalBookmarks,
 } from "../api/appSettingsApi";
 import { AppSettings, LanguageOption } from "../../types/settings";
 import { Bookmark } from "../../types/common";
@@ -18,7 +20,6 @@ export function useAppSettings() {
     fileRandomiser: {
       allowProcessTracking: false,
       randomnessLevel: 50,
-      globalBookmarks: [],
       bookmarkPreference: {
         enabled: false,
         colors: {},
@@ -86,6 +87,42 @@ export function useAppSettings() {
     [fetchGlobalBookmarks],
   );
 
+  // Edits patch the local list rather than refetching every bookmark
+  const upsertGlobalBookmarksSettings = useCallback(
+    async (bookmarks: Bookmark[]) => {
+      try {
+        const stored = await upsertGlobalBookmarks(bookmarks);
+        const hashes = new Set(stored.map((b) => b.hash.toLowerCase()));
+        setGlobalBookmarksState((prev) => [
+          ...prev.filter((b) => !hashes.has(b.hash.toLowerCase())),
+          ...stored,
+        ]);
+        return stored;
+      } catch (err) {
+        console.error("Failed to update global bookmarks:", err);
+        return [];
+      }
+    },
+    [],
+  );
+
+  const removeGlobalBookmarksSettings = useCallback(
+    async (hashes: string[]) => {
+      try {
+        const removed = await removeGlobalBookmarks(hashes);
+        const gone = new Set(hashes.map((h) => h.toLowerCase()));
+        setGlobalBookmarksState((prev) =>
+          prev.filter((b) => !gone.has(b.hash.toLowerCase())),
+        );
+        return removed;
+      } catch (err) {
+        console.error("Failed to remove global bookmarks:", err);
+        return 0;
+      }
+    },
+    [],
+  );
+
   const [systemDark, setSystemDark] = useState(
     window.matchMedia("(prefers-color-scheme: dark)").matches,
   );
@@ -109,6 +146,8 @@ export function useAppSettings() {
     loaded,
     globalBookmarks,
     setGlobalBookmarks: setGlobalBookmarksSettings,
+    upsertGlobalBookmarks: upsertGlobalBookmarksSettings,
+    removeGlobalBookmarks: removeGlobalBookmarksSettings,
     fetchGlobalBookmarks,
   };
 }
@@ -3,8 +3,10 @@ import {
   Checkbox,
   Divider,
   Group,
+  NumberInput,
   Paper,
   Select,
+  SimpleGrid,
   Slider,
   Stack,
   Text,
@@ -16,15 +18,38 @@ import { platform, Platform } from "@tauri-apps/plugin-os";
 import { invoke } from "@tauri-apps/api/core";
 
 import { useAppSettings } from "../hooks/useAppSettings";
+import PickConstraintsEditor from "./pickConstraintsEditor";
+import OpenActionsEditor from "./openActionsEditor";
 import {
+  getTuningProfiles,
   toggleProcessTracking,
   setRandomnessLevel,
 } from "../api/appSettingsApi";
-import { AppSettings, ColorWeightEntry } from "../../types/settings";
+import {
+  AlgorithmTuning,
+  AppSettings,
+  ColorWeightEntry,
+  NewArrivalsBoost,
+  PickMode,
+} from "../../types/settings";
 import { DEFAULT_BOOKMARK_COLOR_OPTIONS } from "../../types/common";
 
 const DEFAULT_ENTRY: ColorWeightEntry = { local: 1.0, global: 1.0 };
 
+/** Editable tuning values and their input step */
+const TUNING_FIELDS: [Exclude<keyof AlgorithmTuning, "profile">, number][] = [
+  ["recencyWindowScale", 0.5],
+  ["recencyWindowMin", 1],
+  ["recencyWindowMax", 10],
+  ["orderSigma", 0.01],
+  ["orderBoost", 1],
+  ["colorStreakLast", 0.05],
+  ["colorStreakRecent", 0.05],
+  ["folderStreakLast", 0.05],
+  ["folderStreakRecent", 0.05],
+  ["hardBlockMax", 1],
+];
+
 const FileRandomiserSettings = () => {
   const { t } = useTranslation();
   const { settings, setSettings } = useAppSettings();
@@ -33,6 +58,7 @@ const FileRandomiserSettings = () => {
   const pref = settings.fileRandomiser.bookmarkPreference;
 
   const [isLinux, setIsLinux] = useState(false);
+  const [tuningProfiles, setTuningProfiles] = useState<AlgorithmTuning[]>([]);
 
   const [localRandomnessValue, setLocalRandomnessValue] = useState<number>(
     settings.fileRandomiser.randomnessLevel ?? 50,
@@ -67,6 +93,12 @@ const FileRandomiserSettings = () => {
     );
   }, [pref?.colors]);
 
+  useEffect(() => {
+    getTuningProfiles()
+      .then(setTuningProfiles)
+      .catch((err) => console.error("Failed to load tuning profiles:", err));
+  }, []);
+
   // ---- OS detection ----
 
   useEffect(() => {
@@ -91,12 +123,40 @@ const FileRandomiserSettings = () => {
     }
   };
 
+  // ---- New arrivals ----
+
+  const newArrivals: NewArrivalsBoost = {
+    enabled: false,
+    boost: 3,
+    halfLifeDays: 7,
+    useModified: false,
+    ...settings.fileRandomiser.newArrivals,
+  };
+
+  const saveNewArrivals = (patch: Partial<NewArrivalsBoost>) =>
+    saveSettings({
+      ...settings,
+      fileRandomiser: {
+        ...settings.fileRandomiser,
+        newArrivals: { ...newArrivals, ...patch },
+      },
+    });
+
+  // ---- Algorithm tuning ----
+
+  const tuning = settings.fileRandomiser.tuning ?? tuningProfiles[0];
+
+  const saveTuning = (updated: AlgorithmTuning) =>
+    saveSettings({
+      ...settings,
+      fileRandomiser: { ...settings.fileRandomiser, tuning: updated },
+    });
+
   // ---- Process tracking ----
 
   const handleProcessTrackingToggle = async (
     checked: boolean,
   ): Promise<void> => {
-    if (isLinux) return;
     try {
       const updated = await toggleProcessTracking(checked);
       setSettings(updated);
@@ -181,25 +241,19 @@ const FileRandomiserSettings = () => {
               {t("fileRandomiserSettings.processTracking.title")}
             </Title>
             <Text size="sm" c="dimmed">
-              {isLinux
-                ? t("fileRandomiserSettings.processTracking.unsupportedOnLinux")
-                : t("fileRandomiserSettings.processTracking.description")}
+              {t("fileRandomiserSettings.processTracking.description")}
             </Text>
+            {isLinux && (
+              <Text size="sm" c="dimmed">
+                {t("fileRandomiserSettings.processTracking.linuxNote")}
+              </Text>
+            )}
             <Checkbox
-              checked={
-                isLinux ? false : settings.fileRandomiser.allowProcessTracking
-              }
-              disabled={isLinux}
+              checked={settings.fileRandomiser.allowProcessTracking}
               label={t("fileRandomiserSettings.processTracking.checkboxLabel")}
-              description={
-                isLinux
-                  ? t(
-                      "fileRandomiserSettings.processTracking.checkboxUnavailable",
-                    )
-                  : t(
-                      "fileRandomiserSettings.processTracking.checkboxDescription",
-                    )
-              }
+              description={t(
+                "fileRandomiserSettings.processTracking.checkboxDescription",
+              )}
               onChange={(e) =>
                 handleProcessTrackingToggle(e.currentTarget.checked)
               }
@@ -376,6 +430,127 @@ const FileRandomiserSettings = () => {
 
           <Divider />
 
+          {/* Pick mode */}
+          <Stack gap="sm">
+            <Title order={4}>
+              {t("fileRandomiserSettings.pickMode.title")}
+            </Title>
+            <Text size="sm" c="dimmed">
+              {t("fileRandomiserSettings.pickMode.description")}
+            </Text>
+            <Select
+              value={settings.fileRandomiser.pickMode ?? "weighted"}
+              onChange={(v) =>
+                saveSettings({
+                  ...settings,
+                  fileRandomiser: {
+                    ...settings.fileRandomiser,
+                    pickMode: v as PickMode,
+                  },
+                })
+              }
+              data={[
+                {
+                  value: "weighted",
+                  label: t("fileRandomiserSettings.pickMode.weighted"),
+                },
+                {
+                  value: "series",
+                  label: t("fileRandomiserSettings.pickMode.series"),
+                },
+              ]}
+              allowDeselect={false}
+              style={{ maxWidth: 240 }}
+            />
+            {settings.fileRandomiser.pickMode === "series" && (
+              <Select
+                label={t("fileRandomiserSettings.pickMode.continueLabel")}
+                value={String(
+                  settings.fileRandomiser.series?.continueProbability ?? 0.75,
+                )}
+                onChange={(v) =>
+                  saveSettings({
+                    ...settings,
+                    fileRandomiser: {
+                      ...settings.fileRandomiser,
+                      series: { continueProbability: Number(v) },
+                    },
+                  })
+                }
+                data={["0", "0.5", "0.75", "0.9", "1"].map((value) => ({
+                  value,
+                  label: `${Math.round(Number(value) * 100)}%`,
+                }))}
+                allowDeselect={false}
+                style={{ maxWidth: 240 }}
+              />
+            )}
+          </Stack>
+
+          <Divider />
+
+          {/* Session constraints */}
+          <Stack gap="sm">
+            <Title order={4}>
+              {t("fileRandomiserSettings.constraints.title")}
+            </Title>
+            <Text size="sm" c="dimmed">
+              {t("fileRandomiserSettings.constraints.description")}
+            </Text>
+            <PickConstraintsEditor
+              constraints={settings.fileRandomiser.pickConstraints ?? []}
+              colorOptions={colorOptions}
+              onChange={(pickConstraints) =>
+                saveSettings({
+                  ...settings,
+                  fileRandomiser: {
+                    ...settings.fileRandomiser,
+                    pickConstraints,
+                  },
+                })
+              }
+            />
+          </Stack>
+
+          <Divider />
+
+          {/* Session idle gap */}
+          <Stack gap="sm">
+            <Title order={4}>
+              {t("fileRandomiserSettings.sessionIdle.title")}
+            </Title>
+            <Text size="sm" c="dimmed">
+              {t("fileRandomiserSettings.sessionIdle.description")}
+            </Text>
+            <Select
+              value={String(settings.fileRandomiser.sessionIdleMinutes ?? 30)}
+              onChange={(v) =>
+                saveSettings({
+                  ...settings,
+                  fileRandomiser: {
+                    ...settings.fileRandomiser,
+                    sessionIdleMinutes: Number(v),
+                  },
+                })
+              }
+              data={["15", "30", "60", "180"]
+                .map((value) => ({
+                  value,
+                  label: t("fileRandomiserSettings.sessionIdle.minutes", {
+                    count: Number(value),
+                  }),
+                }))
+                .concat({
+                  value: "0",
+                  label: t("fileRandomiserSettings.sessionIdle.never"),
+                })}
+              allowDeselect={false}
+              style={{ maxWidth: 240 }}
+            />
+          </Stack>
+
+          <Divider />
+
           {/* History retention */}
           <Stack gap="sm">
             <Title order={4}>
@@ -426,6 +601,177 @@ const FileRandomiserSettings = () => {
 
           <Divider />
 
+          {/* Pick count half-life */}
+          <Stack gap="sm">
+            <Title order={4}>
+              {t("fileRandomiserSettings.pickCountHalfLife.title")}
+            </Title>
+            <Text size="sm" c="dimmed">
+              {t("fileRandomiserSettings.pickCountHalfLife.description")}
+            </Text>
+            <Select
+              value={String(
+                settings.fileRandomiser.pickCountHalfLifeDays ?? 0,
+              )}
+              onChange={(v) =>
+                saveSettings({
+                  ...settings,
+                  fileRandomiser: {
+                    ...settings.fileRandomiser,
+                    pickCountHalfLifeDays: Number(v),
+                  },
+                })
+              }
+              data={[
+                {
+                  value: "30",
+                  label: t("fileRandomiserSettings.pickCountHalfLife.days30"),
+                },
+                {
+                  value: "90",
+                  label: t("fileRandomiserSettings.pickCountHalfLife.days90"),
+                },
+                {
+                  value: "180",
+                  label: t("fileRandomiserSettings.pickCountHalfLife.days180"),
+                },
+                {
+                  value: "365",
+                  label: t("fileRandomiserSettings.pickCountHalfLife.days365"),
+                },
+                {
+                  value: "0",
+                  label: t("fileRandomiserSettings.pickCountHalfLife.never"),
+                },
+              ]}
+              allowDeselect={false}
+              style={{ maxWidth: 240 }}
+            />
+          </Stack>
+
+          <Divider />
+
+          {/* New arrivals */}
+          <Stack gap="sm">
+            <Title order={4}>
+              {t("fileRandomiserSettings.newArrivals.title")}
+            </Title>
+            <Text size="sm" c="dimmed">
+              {t("fileRandomiserSettings.newArrivals.description")}
+            </Text>
+            <Checkbox
+              checked={newArrivals.enabled}
+              label={t("fileRandomiserSettings.newArrivals.checkboxLabel")}
+              onChange={(e) =>
+                saveNewArrivals({ enabled: e.currentTarget.checked })
+              }
+              size="md"
+              color="blue"
+            />
+            <Group gap="md">
+              <Select
+                label={t("fileRandomiserSettings.newArrivals.boost")}
+                value={String(newArrivals.boost)}
+                onChange={(v) => saveNewArrivals({ boost: Number(v) })}
+                data={["1.5", "2", "3", "5"].map((value) => ({
+                  value,
+                  label: `${value}×`,
+                }))}
+                disabled={!newArrivals.enabled}
+                allowDeselect={false}
+                style={{ maxWidth: 160 }}
+              />
+              <Select
+                label={t("fileRandomiserSettings.newArrivals.halfLife")}
+                value={String(newArrivals.halfLifeDays)}
+                onChange={(v) => saveNewArrivals({ halfLifeDays: Number(v) })}
+                data={["3", "7", "14", "30"].map((value) => ({
+                  value,
+                  label: t("fileRandomiserSettings.newArrivals.days", {
+                    count: Number(value),
+                  }),
+                }))}
+                disabled={!newArrivals.enabled}
+                allowDeselect={false}
+                style={{ maxWidth: 160 }}
+              />
+            </Group>
+            <Checkbox
+              checked={newArrivals.useModified}
+              label={t("fileRandomiserSettings.newArrivals.useModified")}
+              onChange={(e) =>
+                saveNewArrivals({ useModified: e.currentTarget.checked })
+              }
+              disabled={!newArrivals.enabled}
+              size="md"
+              color="blue"
+            />
+          </Stack>
+
+          <Divider />
+
+          {/* Algorithm tuning */}
+          {tuning && (
+            <Stack gap="sm">
+              <Title order={4}>
+                {t("fileRandomiserSettings.tuning.title")}
+              </Title>
+              <Text size="sm" c="dimmed">
+                {t("fileRandomiserSettings.tuning.description")}
+              </Text>
+              <Select
+                label={t("fileRandomiserSettings.tuning.profile")}
+                value={tuning.profile}
+                onChange={(v) => {
+                  const profile = tuningProfiles.find((p) => p.profile === v);
+                  if (profile) saveTuning(profile);
+                }}
+                data={[
+                  ...tuningProfiles.map((p) => ({
+                    value: p.profile,
+                    label: t(
+                      `fileRandomiserSettings.tuning.profiles.${p.profile}`,
+                    ),
+                  })),
+                  ...(tuning.profile === "custom"
+                    ? [
+                        {
+                          value: "custom",
+                          label: t(
+                            "fileRandomiserSettings.tuning.profiles.custom",
+                          ),
+                          disabled: true,
+                        },
+                      ]
+                    : []),
+                ]}
+                allowDeselect={false}
+                style={{ maxWidth: 240 }}
+              />
+              <SimpleGrid cols={{ base: 2, sm: 3 }}>
+                {TUNING_FIELDS.map(([key, step]) => (
+                  // Keyed on the saved value so a rejected edit resets
+                  <NumberInput
+                    key={`${key}-${tuning[key]}`}
+                    label={t(`fileRandomiserSettings.tuning.fields.${key}`)}
+                    defaultValue={tuning[key]}
+                    step={step}
+                    min={0}
+                    decimalScale={step < 1 ? 2 : 0}
+                    onBlur={(e) => {
+                      const value = Number(e.currentTarget.value);
+                      if (!Number.isNaN(value) && value !== tuning[key]) {
+                        saveTuning({ ...tuning, [key]: value });
+                      }
+                    }}
+                  />
+                ))}
+              </SimpleGrid>
+            </Stack>
+          )}
+
+          <Divider />
+
           {/* Persist recency */}
           <Stack gap="sm">
             <Title order={4}>
@@ -477,6 +823,24 @@ const FileRandomiserSettings = () => {
               color="orange"
             />
           </Stack>
+
+          <Divider />
+
+          {/* Open actions */}
+          <Stack gap="sm">
+            <Title order={4}>
+              {t("fileRandomiserSettings.openActions.title")}
+            </Title>
+            <Text size="sm" c="dimmed">
+              {t("fileRandomiserSettings.openActions.description")}
+            </Text>
+            <OpenActionsEditor
+              rules={settings.openActions ?? []}
+              onChange={(openActions) =>
+                saveSettings({ ...settings, openActions })
+              }
+            />
+          </Stack>
         </Stack>
       </Paper>
     </Box>
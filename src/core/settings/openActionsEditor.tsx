@@ -0,0 +1,159 @@
+import {
+  ActionIcon,
+  Button,
+  Group,
+  Select,
+  Stack,
+  Text,
+  TextInput,
+} from "@mantine/core";
+import { PlusIcon, TrashIcon } from "@phosphor-icons/react";
+import { useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import { getPresets } from "../api/presetsApi";
+import { OpenAction, OpenActionRule } from "../../types/settings";
+import { RandomiserPreset } from "../../types/filerandomiser";
+
+interface OpenActionsEditorProps {
+  rules: OpenActionRule[];
+  onChange: (rules: OpenActionRule[]) => void;
+}
+
+type ActionKind = OpenAction["kind"];
+
+const ACTION_KINDS: ActionKind[] = [
+  "default",
+  "application",
+  "command",
+  "reveal",
+  "copyPath",
+  "openFolder",
+];
+
+const OpenActionsEditor = ({ rules, onChange }: OpenActionsEditorProps) => {
+  const { t } = useTranslation();
+  const oa = "fileRandomiserSettings.openActions";
+  const [presets, setPresets] = useState<RandomiserPreset[]>([]);
+  const [extensions, setExtensions] = useState("");
+  const [presetId, setPresetId] = useState<string | null>(null);
+  const [kind, setKind] = useState<ActionKind>("application");
+  const [value, setValue] = useState("");
+
+  useEffect(() => {
+    getPresets()
+      .then((listing) => setPresets(listing.presets))
+      .catch((err) => console.error("Failed to load presets:", err));
+  }, []);
+
+  const presetName = (id: string) =>
+    presets.find((p) => p.id === id)?.name ?? id;
+
+  const describeAction = (action: OpenAction) => {
+    switch (action.kind) {
+      case "application":
+        return t(`${oa}.describe.application`, { path: action.path });
+      case "command":
+        return t(`${oa}.describe.command`, { template: action.template });
+      default:
+        return t(`${oa}.kinds.${action.kind}`);
+    }
+  };
+
+  const describe = (rule: OpenActionRule) => {
+    const files =
+      rule.extensions.length > 0
+        ? rule.extensions.map((e) => `.${e}`).join(", ")
+        : t(`${oa}.anyFile`);
+    const scope = rule.presetId
+      ? t(`${oa}.inPreset`, { preset: presetName(rule.presetId) })
+      : "";
+    return `${files}${scope} → ${describeAction(rule.action)}`;
+  };
+
+  const needsValue = kind === "application" || kind === "command";
+
+  const add = () => {
+    const trimmed = value.trim();
+    if (needsValue && !trimmed) return;
+    const action: OpenAction =
+      kind === "application"
+        ? { kind, path: trimmed }
+        : kind === "command"
+          ? { kind, template: trimmed }
+          : { kind };
+    const rule: OpenActionRule = {
+      extensions: extensions
+        .split(/[\s,]+/)
+        .map((e) => e.replace(/^\./, "").toLowerCase())
+        .filter(Boolean),
+      presetId,
+      action,
+    };
+    onChange([...rules, rule]);
+    setExtensions("");
+    setValue("");
+  };
+
+  return (
+    <Stack gap="xs">
+      {rules.map((rule, i) => (
+        <Group key={i} gap="xs" wrap="nowrap">
+          <Text size="sm" style={{ flex: 1, wordBreak: "break-all" }}>
+            {describe(rule)}
+          </Text>
+          <ActionIcon
+            variant="subtle"
+            color="red"
+            onClick={() => onChange(rules.filter((_, j) => j !== i))}
+          >
+            <TrashIcon size={16} />
+          </ActionIcon>
+        </Group>
+      ))}
+
+      <Group gap="xs" align="flex-end">
+        <TextInput
+          value={extensions}
+          onChange={(e) => setExtensions(e.currentTarget.value)}
+          placeholder={t(`${oa}.extensionsPlaceholder`)}
+          style={{ width: 160 }}
+        />
+        <Select
+          value={presetId}
+          onChange={setPresetId}
+          data={presets.map((p) => ({ value: p.id, label: p.name }))}
+          placeholder={t(`${oa}.anyPreset`)}
+          clearable
+          style={{ width: 180 }}
+        />
+        <Select
+          value={kind}
+          onChange={(v) => v && setKind(v as ActionKind)}
+          data={ACTION_KINDS.map((value) => ({
+            value,
+            label: t(`${oa}.kinds.${value}`),
+          }))}
+          allowDeselect={false}
+          style={{ width: 200 }}
+        />
+        {needsValue && (
+          <TextInput
+            value={value}
+            onChange={(e) => setValue(e.currentTarget.value)}
+            placeholder={t(`${oa}.placeholders.${kind}`)}
+            style={{ flex: 1, minWidth: 200 }}
+          />
+        )}
+        <Button
+          variant="light"
+          leftSection={<PlusIcon size={14} />}
+          onClick={add}
+        >
+          {t(`${oa}.add`)}
+        </Button>
+      </Group>
+    </Stack>
+  );
+};
+
+export default OpenActionsEditor;
@@ -0,0 +1,130 @@
+import {
+  ActionIcon,
+  Button,
+  Group,
+  NumberInput,
+  Select,
+  Stack,
+  Text,
+} from "@mantine/core";
+import { PlusIcon, TrashIcon } from "@phosphor-icons/react";
+import { useState } from "react";
+import { useTranslation } from "react-i18next";
+import { PickConstraint } from "../../types/settings";
+import { BookmarkColorOption } from "../../types/common";
+
+interface PickConstraintsEditorProps {
+  constraints: PickConstraint[];
+  colorOptions: BookmarkColorOption[];
+  onChange: (constraints: PickConstraint[]) => void;
+}
+
+type ConstraintKind = PickConstraint["kind"];
+
+const PickConstraintsEditor = ({
+  constraints,
+  colorOptions,
+  onChange,
+}: PickConstraintsEditorProps) => {
+  const { t } = useTranslation();
+  const [kind, setKind] = useState<ConstraintKind>("maxPerFolder");
+  const [count, setCount] = useState<number>(2);
+  const [color, setColor] = useState<string | null>(
+    colorOptions[0]?.hex ?? null,
+  );
+
+  const colorLabel = (hex: string) =>
+    colorOptions.find((c) => c.hex.toLowerCase() === hex.toLowerCase())
+      ?.label ?? hex;
+
+  const describe = (constraint: PickConstraint) => {
+    switch (constraint.kind) {
+      case "maxPerFolder":
+        return t("fileRandomiserSettings.constraints.maxPerFolder", {
+          count: constraint.max,
+        });
+      case "colorEvery":
+        return t("fileRandomiserSettings.constraints.colorEvery", {
+          color: colorLabel(constraint.color),
+          count: constraint.every,
+        });
+      case "noRepeatExtension":
+        return t("fileRandomiserSettings.constraints.noRepeatExtension");
+    }
+  };
+
+  const add = () => {
+    const constraint: PickConstraint | null =
+      kind === "maxPerFolder"
+        ? { kind, max: count }
+        : kind === "colorEvery"
+          ? color
+            ? { kind, color, every: count }
+            : null
+          : { kind };
+    if (constraint) onChange([...constraints, constraint]);
+  };
+
+  return (
+    <Stack gap="xs">
+      {constraints.map((constraint, i) => (
+        <Group key={i} gap="xs" wrap="nowrap">
+          <Text size="sm" style={{ flex: 1 }}>
+            {describe(constraint)}
+          </Text>
+          <ActionIcon
+            variant="subtle"
+            color="red"
+            onClick={() => onChange(constraints.filter((_, j) => j !== i))}
+          >
+            <TrashIcon size={16} />
+          </ActionIcon>
+        </Group>
+      ))}
+
+      <Group gap="xs" align="flex-end">
+        <Select
+          value={kind}
+          onChange={(v) => v && setKind(v as ConstraintKind)}
+          data={(
+            ["maxPerFolder", "colorEvery", "noRepeatExtension"] as const
+          ).map((value) => ({
+            value,
+            label: t(`fileRandomiserSettings.constraints.kinds.${value}`),
+          }))}
+          allowDeselect={false}
+          style={{ width: 220 }}
+        />
+        {kind === "colorEvery" && (
+          <Select
+            value={color}
+            onChange={setColor}
+            data={colorOptions.map(({ hex, label }) => ({
+              value: hex,
+              label,
+            }))}
+            allowDeselect={false}
+            style={{ width: 140 }}
+          />
+        )}
+        {kind !== "noRepeatExtension" && (
+          <NumberInput
+            value={count}
+            onChange={(v) => setCount(Math.max(1, Number(v) || 1))}
+            min={1}
+            style={{ width: 90 }}
+          />
+        )}
+        <Button
+          variant="light"
+          leftSection={<PlusIcon size={14} />}
+          onClick={add}
+        >
+          {t("fileRandomiserSettings.constraints.add")}
+        </Button>
+      </Group>
+    </Stack>
+  );
+};
+
+export default PickConstraintsEditor;
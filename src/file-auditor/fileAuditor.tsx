@@ -62,7 +62,12 @@ const clearSession = () => localStorage.removeItem(SESSION_KEY);
 
 const FileAuditor = () => {
   const { t } = useTranslation();
-  const { settings, globalBookmarks, setGlobalBookmarks } = useAppSettings();
+  const {
+    settings,
+    globalBookmarks,
+    upsertGlobalBookmarks,
+    removeGlobalBookmarks,
+  } = useAppSettings();
 
   const [files, setFiles] = useState<AuditFileEntry[]>([]);
   const [index, setIndex] = useState(0);
@@ -271,24 +276,22 @@ const FileAuditor = () => {
       const file = filesRef.current[indexRef.current];
       if (!file) return;
       const color = bookmarkColors[slot - 1]?.hex ?? null;
-      const existing = globalBookmarks ?? [];
-      const without = existing.filter((b) => b.hash !== file.hash);
-      await setGlobalBookmarks(
-        color
-          ? [...without, { path: file.path, hash: file.hash, color }]
-          : without,
-      );
+      if (color) {
+        await upsertGlobalBookmarks([
+          { path: file.path, hash: file.hash, color },
+        ]);
+      } else {
+        await removeGlobalBookmarks([file.hash]);
+      }
     },
-    [bookmarkColors, globalBookmarks, setGlobalBookmarks],
+    [bookmarkColors, upsertGlobalBookmarks, removeGlobalBookmarks],
   );
 
   const clearBookmark = useCallback(async () => {
     const file = filesRef.current[indexRef.current];
     if (!file) return;
-    await setGlobalBookmarks(
-      (globalBookmarks ?? []).filter((b) => b.hash !== file.hash),
-    );
-  }, [globalBookmarks, setGlobalBookmarks]);
+    await removeGlobalBookmarks([file.hash]);
+  }, [removeGlobalBookmarks]);
 
   useEffect(() => {
     if (!isAuditing) return;
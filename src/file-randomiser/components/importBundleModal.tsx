@@ -0,0 +1,120 @@
+import {
+  Alert,
+  Badge,
+  Button,
+  Group,
+  Modal,
+  Stack,
+  Text,
+  TextInput,
+} from "@mantine/core";
+import { useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import * as presetApi from "../../core/api/presetsApi";
+import {
+  PresetBundlePreview,
+  RandomiserPreset,
+} from "../../types/filerandomiser";
+
+interface ImportBundleModalProps {
+  preview: PresetBundlePreview | null;
+  onClose: () => void;
+  onImported: (preset: RandomiserPreset) => void;
+}
+
+/** Lets the user point each bundle root at a local folder before importing */
+const ImportBundleModal = ({
+  preview,
+  onClose,
+  onImported,
+}: ImportBundleModalProps) => {
+  const { t } = useTranslation();
+  const [targets, setTargets] = useState<Record<string, string>>({});
+  const [importing, setImporting] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    setTargets(
+      Object.fromEntries(preview?.roots.map((r) => [r.path, r.path]) ?? []),
+    );
+    setError(null);
+  }, [preview]);
+
+  const handleImport = async () => {
+    if (!preview) return;
+    const remaps = preview.roots
+      .map((r) => ({ from: r.path, to: (targets[r.path] ?? "").trim() }))
+      .filter((r) => r.to && r.to !== r.from);
+
+    setImporting(true);
+    try {
+      const preset = await presetApi.importPresetBundle(preview.bundle, remaps);
+      onImported(preset);
+    } catch (e) {
+      setError(String(e));
+    } finally {
+      setImporting(false);
+    }
+  };
+
+  return (
+    <Modal
+      opened={preview !== null}
+      onClose={onClose}
+      title={t("fileRandomiser.importBundle.title", {
+        name: preview?.bundle.preset.name ?? "",
+      })}
+      size="lg"
+    >
+      {preview && (
+        <Stack gap="sm">
+          <Text size="sm" c="dimmed">
+            {t("fileRandomiser.importBundle.description")}
+          </Text>
+
+          {preview.roots.map((root) => (
+            <Stack key={root.path} gap={4}>
+              <Group gap="xs" wrap="nowrap">
+                <Text size="sm" truncate>
+                  {root.path}
+                </Text>
+                <Badge color={root.exists ? "green" : "red"} variant="light">
+                  {root.exists
+                    ? t("fileRandomiser.importBundle.found")
+                    : t("fileRandomiser.importBundle.missing")}
+                </Badge>
+              </Group>
+              <TextInput
+                size="sm"
+                value={targets[root.path] ?? ""}
+                placeholder={t("fileRandomiser.importBundle.targetPlaceholder")}
+                onChange={(e) => {
+                  const value = e.currentTarget.value;
+                  setTargets((prev) => ({ ...prev, [root.path]: value }));
+                }}
+              />
+            </Stack>
+          ))}
+
+          {preview.idConflict && (
+            <Alert color="yellow">
+              {t("fileRandomiser.importBundle.idConflict")}
+            </Alert>
+          )}
+          {error && <Alert color="red">{error}</Alert>}
+
+          <Group justify="flex-end" gap="xs">
+            <Button variant="default" onClick={onClose}>
+              {t("fileRandomiser.importBundle.cancel")}
+            </Button>
+            <Button loading={importing} onClick={handleImport}>
+              {t("fileRandomiser.importBundle.import")}
+            </Button>
+          </Group>
+        </Stack>
+      )}
+    </Modal>
+  );
+};
+
+export default ImportBundleModal;
@@ -6,7 +6,12 @@ import {
   TextInput,
   Tooltip,
 } from "@mantine/core";
-import { FloppyDiskIcon, FolderOpenIcon } from "@phosphor-icons/react";
+import {
+  DownloadSimpleIcon,
+  ExportIcon,
+  FloppyDiskIcon,
+  FolderOpenIcon,
+} from "@phosphor-icons/react";
 import { RandomiserPreset } from "../../types/filerandomiser";
 import { useTranslation } from "react-i18next";
 
@@ -19,6 +24,8 @@ interface PresetControlsProps {
   onSelect: (preset: RandomiserPreset) => void;
   onSave: () => void;
   onOpenFolder: () => void;
+  onExportBundle: () => void;
+  onImportBundle: () => void;
   onPresetClear: () => void;
 }
 
@@ -31,6 +38,8 @@ const PresetControls = ({
   onSelect,
   onSave,
   onOpenFolder,
+  onExportBundle,
+  onImportBundle,
   onPresetClear,
 }: PresetControlsProps) => {
   const { t } = useTranslation();
@@ -95,6 +104,19 @@ const PresetControls = ({
         >
           {t("fileRandomiser.presetControls.openPresetsFolder")}
         </Menu.Item>
+        <Menu.Item
+          leftSection={<ExportIcon size={14} />}
+          disabled={!appliedPreset}
+          onClick={onExportBundle}
+        >
+          {t("fileRandomiser.presetControls.exportBundle")}
+        </Menu.Item>
+        <Menu.Item
+          leftSection={<DownloadSimpleIcon size={14} />}
+          onClick={onImportBundle}
+        >
+          {t("fileRandomiser.presetControls.importBundle")}
+        </Menu.Item>
       </Menu.Dropdown>
     </Menu>
   );
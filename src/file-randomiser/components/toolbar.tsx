@@ -11,6 +11,7 @@ import {
 import {
   FolderPlusIcon,
   ArrowsClockwiseIcon,
+  ArrowCounterClockwiseIcon,
   ShuffleIcon,
   MagnifyingGlassIcon,
   XCircleIcon,
@@ -29,6 +30,7 @@ interface ToolbarProps {
   onAddPath: () => void;
   onCrawl: () => void;
   onPickFile: () => void;
+  onUndoPick: () => void;
   onShuffleChange: (val: boolean) => void;
   onTrackingChange: (val: boolean) => void;
   onQueryChange: (val: string) => void;
@@ -44,6 +46,7 @@ const Toolbar = ({
   onAddPath,
   onCrawl,
   onPickFile,
+  onUndoPick,
   onShuffleChange,
   onTrackingChange,
   onQueryChange,
@@ -105,6 +108,22 @@ const Toolbar = ({
             : t("fileRandomiser.toolbar.nextFile")}
         </Button>
       </Tooltip>
+      {shuffle && (
+        <Tooltip
+          label={t("fileRandomiser.toolbar.undoPick")}
+          withArrow
+          position="bottom"
+        >
+          <ActionIcon
+            variant="subtle"
+            size="md"
+            onClick={onUndoPick}
+            disabled={hasStartedTracking}
+          >
+            <ArrowCounterClockwiseIcon size={16} />
+          </ActionIcon>
+        </Tooltip>
+      )}
       <Tooltip
         label={t("fileRandomiser.stats.title")}
         withArrow
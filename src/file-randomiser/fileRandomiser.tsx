@@ -1,8 +1,10 @@
 import {
   ActionIcon,
+  Alert,
   Box,
   Button,
   Group,
+  Loader,
   LoadingOverlay,
   Stack,
   Text,
@@ -28,6 +30,9 @@ import {
   PresetState,
   FileTreeNode,
   FileEntry,
+  CrawlProgress,
+  InvalidPresetFile,
+  PresetBundlePreview,
 } from "../types/filerandomiser";
 import { TrashIcon } from "@phosphor-icons/react";
 import Toolbar from "./components/toolbar";
@@ -36,11 +41,18 @@ import FileTree, { FileTreeHandle } from "./components/tree/fileTree";
 import FiltersPanel from "./components/filter/filtersPanel";
 import ItemActions from "./components/tree/itemActions";
 import PresetControls from "./components/presetControls";
+import ImportBundleModal from "./components/importBundleModal";
 import Section from "../common/section";
 
 const FileRandomiser = () => {
-  const { settings, setSettings, globalBookmarks, setGlobalBookmarks, loaded } =
-    useAppSettings();
+  const {
+    settings,
+    setSettings,
+    globalBookmarks,
+    upsertGlobalBookmarks,
+    removeGlobalBookmarks,
+    loaded,
+  } = useAppSettings();
   const { t } = useTranslation();
 
   const {
@@ -77,8 +89,21 @@ const FileRandomiser = () => {
   const lastFileOpenedAtRef = useRef<number>(0);
 
   const [showLoading, setShowLoading] = useState(false);
+  const [crawlProgress, setCrawlProgress] = useState<CrawlProgress | null>(
+    null,
+  );
+  // Only the latest crawl may clear the crawling flag; older ones were
+  // superseded and resolve null
+  const crawlGenerationRef = useRef(0);
   const loadingTimeoutRef = useRef<number | null>(null);
   const [hasStartedTracking, setHasStartedTracking] = useState(false);
+  // File opened while tracking whose close can't be detected
+  const [untrackedPath, setUntrackedPath] = useState<string | null>(null);
+  const [invalidPresets, setInvalidPresets] = useState<InvalidPresetFile[]>(
+    [],
+  );
+  const [bundlePreview, setBundlePreview] =
+    useState<PresetBundlePreview | null>(null);
   const [historyHiddenBefore, setHistoryHiddenBefore] = useState<Date>(() => {
     const d = new Date();
     d.setHours(0, 0, 0, 0);
@@ -108,9 +133,15 @@ const FileRandomiser = () => {
     console.log("[tracking] tracking state changed to:", tracking);
   }, [tracking]);
 
+  const refreshPresets = () =>
+    presetApi.getPresets().then(({ presets, invalid }) => {
+      setPresets(presets);
+      setInvalidPresets(invalid);
+    });
+
   useEffect(() => {
     updateAndRefreshData();
-    presetApi.getPresets().then(setPresets);
+    refreshPresets();
   }, []);
 
   useEffect(() => {
@@ -206,16 +237,13 @@ const FileRandomiser = () => {
       await randomiserApi.updateFileBookmarksBulk(hashes, color, isGlobal);
 
       if (isGlobal) {
-        const existing = globalBookmarks ?? [];
-        const withoutThese = existing.filter((b) => !hashes.includes(b.hash));
-        const nextGlobal =
-          color === null
-            ? withoutThese
-            : [
-                ...withoutThese,
-                ...files.map((f) => ({ path: f.path, hash: f.hash, color })),
-              ];
-        await setGlobalBookmarks(nextGlobal);
+        if (color === null) {
+          await removeGlobalBookmarks(hashes);
+        } else {
+          await upsertGlobalBookmarks(
+            files.map((f) => ({ path: f.path, hash: f.hash, color })),
+          );
+        }
       } else {
         const preset = lastAppliedPresetRef.current;
         const existing = preset?.bookmarks ?? presetState.bookmarks ?? [];
@@ -247,24 +275,25 @@ const FileRandomiser = () => {
 
       setBookmarksDirty(true);
     },
-    [globalBookmarks, setGlobalBookmarks, setData, presetState.bookmarks],
+    [
+      upsertGlobalBookmarks,
+      removeGlobalBookmarks,
+      setData,
+      presetState.bookmarks,
+    ],
   );
 
   const handleBookmarkChangeGlobal = useCallback(
     async (file: FileEntry, color: string | null, isGlobal = true) => {
       if (isGlobal) {
         // --- GLOBAL BOOKMARK ---
-        const existing = globalBookmarks ?? [];
-        const nextGlobalBookmarks =
-          color === null
-            ? existing.filter((b) => b.hash !== file.hash)
-            : [
-                ...existing.filter((b) => b.hash !== file.hash),
-                { path: file.path, hash: file.hash, color },
-              ];
-
-        // persist global bookmarks
-        await setGlobalBookmarks(nextGlobalBookmarks);
+        if (color === null) {
+          await removeGlobalBookmarks([file.hash]);
+        } else {
+          await upsertGlobalBookmarks([
+            { path: file.path, hash: file.hash, color },
+          ]);
+        }
 
         // update file immediately
         setData((prev) => ({
@@ -326,7 +355,12 @@ const FileRandomiser = () => {
         setBookmarksDirty(true);
       }
     },
-    [globalBookmarks, setGlobalBookmarks, setData, presetState.bookmarks],
+    [
+      upsertGlobalBookmarks,
+      removeGlobalBookmarks,
+      setData,
+      presetState.bookmarks,
+    ],
   );
 
   const handleBookmarkChange = useCallback(
@@ -364,12 +398,14 @@ const FileRandomiser = () => {
   };
 
   const handleCrawl = async () => {
+    const generation = ++crawlGenerationRef.current;
+    setCrawlProgress(null);
     setIsCrawling(true);
     try {
-      const globalBms = globalBookmarks ?? [];
       const localBms =
         lastAppliedPresetRef.current?.bookmarks ?? presetState.bookmarks ?? [];
-      await randomiserApi.crawlPaths(globalBms, localBms);
+      const crawled = await randomiserApi.crawlPaths(localBms);
+      if (crawled === null) return;
       const latest = await randomiserApi.getAppState();
 
       const structureChanged =
@@ -387,10 +423,14 @@ const FileRandomiser = () => {
         setFreshCrawl(true);
       }
     } finally {
-      setIsCrawling(false);
+      if (generation === crawlGenerationRef.current) setIsCrawling(false);
     }
   };
 
+  const handleCancelCrawl = () => {
+    randomiserApi.cancelCrawl().catch(console.error);
+  };
+
   const handlePickFile = useCallback(async () => {
     if (!data.files.length) return;
 
@@ -442,6 +482,11 @@ const FileRandomiser = () => {
 
   handlePickFileRef.current = handlePickFile;
 
+  const handleUndoPick = async () => {
+    const undone = await randomiserApi.undoLastPick();
+    if (undone) updateAndRefreshData();
+  };
+
   useEffect(() => {
     let unlisten: (() => void) | null = null;
     console.log("[tracking] registering file-closed listener (once)");
@@ -491,6 +536,38 @@ const FileRandomiser = () => {
     return () => unlisten?.();
   }, []);
 
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+    listen<CrawlProgress>("crawl-progress", (event) => {
+      setCrawlProgress(event.payload);
+    }).then((fn) => {
+      unlisten = fn;
+    });
+    return () => unlisten?.();
+  }, []);
+
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+    listen<string>("tracking-unavailable", (event) => {
+      setUntrackedPath(event.payload);
+    }).then((fn) => {
+      unlisten = fn;
+    });
+    return () => unlisten?.();
+  }, []);
+
+  // Picks skip and drop files deleted or moved since the last crawl; the
+  // refresh after the pick picks up the shorter file list
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+    listen<string[]>("files-missing", (event) => {
+      console.warn("[pick] skipped missing files:", event.payload);
+    }).then((fn) => {
+      unlisten = fn;
+    });
+    return () => unlisten?.();
+  }, []);
+
   const toggleTreeCollapsed = () => {
     setTreeCollapsed(!treeCollapsed);
   };
@@ -546,6 +623,7 @@ const FileRandomiser = () => {
     });
 
     randomiserApi.setPresetPathWeights(preset.pathWeights ?? {});
+    randomiserApi.setActivePreset(preset.id, preset.name);
 
     await updateFiltersAndCrawl({
       ...data,
@@ -590,7 +668,7 @@ const FileRandomiser = () => {
     }));
     setBookmarksDirty(false);
 
-    presetApi.getPresets().then(setPresets);
+    refreshPresets();
   };
 
   const savePresetAs = async () => {
@@ -607,6 +685,7 @@ const FileRandomiser = () => {
     };
 
     await presetApi.savePreset(newPreset);
+    randomiserApi.setActivePreset(id, newPreset.name);
 
     // Update ref and state
     lastAppliedPresetRef.current = newPreset;
@@ -619,11 +698,12 @@ const FileRandomiser = () => {
     });
     setBookmarksDirty(false);
 
-    presetApi.getPresets().then(setPresets);
+    refreshPresets();
   };
 
   const handleClearPreset = async () => {
     lastAppliedPresetRef.current = null;
+    randomiserApi.setActivePreset(null, null);
 
     setPresetState({
       currentId: null,
@@ -643,6 +723,22 @@ const FileRandomiser = () => {
     presetApi.openPresetsFolder();
   };
 
+  const exportPresetBundle = () => {
+    const preset = lastAppliedPresetRef.current;
+    if (!preset) return;
+    presetApi.exportPresetBundle(preset.id).catch(console.error);
+  };
+
+  const openPresetBundle = () => {
+    presetApi.openPresetBundle().then(setBundlePreview).catch(console.error);
+  };
+
+  const handleBundleImported = async (preset: RandomiserPreset) => {
+    setBundlePreview(null);
+    await refreshPresets();
+    await applyPreset(preset);
+  };
+
   const isPresetDirty = (
     preset: RandomiserPreset | null,
     currentData: AppStateData,
@@ -776,7 +872,21 @@ const FileRandomiser = () => {
         visible={showLoading}
         zIndex={1000}
         overlayProps={{ blur: 2 }}
-        loaderProps={{ type: "dots" }}
+        loaderProps={{
+          children: (
+            <Stack align="center" gap="xs">
+              <Loader type="dots" />
+              {crawlProgress && (
+                <Text size="sm">
+                  {t("fileRandomiser.crawl.progress", crawlProgress)}
+                </Text>
+              )}
+              <Button size="xs" variant="light" onClick={handleCancelCrawl}>
+                {t("fileRandomiser.crawl.cancel")}
+              </Button>
+            </Stack>
+          ),
+        }}
       />
       <Stack h="100%" gap="md">
         <Toolbar
@@ -788,6 +898,7 @@ const FileRandomiser = () => {
           onAddPath={handleAddPath}
           onCrawl={handleCrawl}
           onPickFile={handlePickFile}
+          onUndoPick={handleUndoPick}
           onShuffleChange={setShuffle}
           onTrackingChange={setTracking}
           onQueryChange={setQuery}
@@ -800,12 +911,47 @@ const FileRandomiser = () => {
               onSelect={applyPreset}
               onSave={savePreset}
               onOpenFolder={openPresetsFolder}
+              onExportBundle={exportPresetBundle}
+              onImportBundle={openPresetBundle}
               onPresetClear={handleClearPreset}
               appliedPreset={debouncedAppliedPreset}
             />
           }
         />
 
+        <ImportBundleModal
+          preview={bundlePreview}
+          onClose={() => setBundlePreview(null)}
+          onImported={handleBundleImported}
+        />
+
+        {invalidPresets.length > 0 && (
+          <Alert
+            color="red"
+            title={t("fileRandomiser.invalidPresets")}
+            withCloseButton
+            onClose={() => setInvalidPresets([])}
+          >
+            <Stack gap="xs">
+              {invalidPresets.map((f) => (
+                <Text key={f.fileName} size="sm">
+                  {f.fileName}: {f.error}
+                </Text>
+              ))}
+            </Stack>
+          </Alert>
+        )}
+
+        {untrackedPath && (
+          <Alert
+            color="yellow"
+            withCloseButton
+            onClose={() => setUntrackedPath(null)}
+          >
+            {t("fileRandomiser.trackingUnavailable", { path: untrackedPath })}
+          </Alert>
+        )}
+
         {/* Filters */}
         <FiltersPanel data={data} updateData={updateFiltersAndCrawl} />
 
@@ -0,0 +1,124 @@
+import { Badge, Button, Group, ScrollArea, Table, Text } from "@mantine/core";
+import { useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import { listen } from "@tauri-apps/api/event";
+import * as randomiserApi from "../../core/api/fileRandomiserApi";
+import { SessionSummary } from "../../types/filerandomiser";
+import { fmt } from "./utils";
+import { Section } from "./Section";
+
+function formatDuration(seconds: number) {
+  const minutes = Math.round(seconds / 60);
+  if (minutes < 60) return `${minutes}m`;
+  return `${Math.floor(minutes / 60)}h ${minutes % 60}m`;
+}
+
+export function SessionsSection() {
+  const { t } = useTranslation();
+  const ss = "fileRandomiser.statsWindow.sessions";
+  const [summaries, setSummaries] = useState<SessionSummary[]>([]);
+
+  const refresh = () =>
+    randomiserApi.getSessionSummaries().then(setSummaries).catch(console.error);
+
+  useEffect(() => {
+    refresh();
+    let unlistenPick: (() => void) | null = null;
+    let unlistenUndo: (() => void) | null = null;
+    listen("file-picked", () => refresh()).then((fn) => {
+      unlistenPick = fn;
+    });
+    listen("pick-undone", () => refresh()).then((fn) => {
+      unlistenUndo = fn;
+    });
+    return () => {
+      unlistenPick?.();
+      unlistenUndo?.();
+    };
+  }, []);
+
+  const open = summaries[0]?.session.endedAt === null;
+
+  return (
+    <Section title={t(`${ss}.title`)} sub={t(`${ss}.sub`)}>
+      <Group gap="xs">
+        <Button
+          size="xs"
+          variant="light"
+          onClick={() => randomiserApi.startPickSession().then(refresh)}
+        >
+          {t(`${ss}.start`)}
+        </Button>
+        <Button
+          size="xs"
+          variant="subtle"
+          disabled={!open}
+          onClick={() => randomiserApi.endPickSession().then(refresh)}
+        >
+          {t(`${ss}.end`)}
+        </Button>
+      </Group>
+      {summaries.length === 0 ? (
+        <Text size="sm" c="dimmed">
+          {t(`${ss}.empty`)}
+        </Text>
+      ) : (
+        <ScrollArea.Autosize mah={320}>
+          <Table
+            striped
+            highlightOnHover
+            withTableBorder
+            withColumnBorders
+            style={{ fontSize: 13 }}
+          >
+            <Table.Thead>
+              <Table.Tr>
+                <Table.Th>{t(`${ss}.started`)}</Table.Th>
+                <Table.Th>{t(`${ss}.preset`)}</Table.Th>
+                <Table.Th>{t(`${ss}.strategy`)}</Table.Th>
+                <Table.Th ta="right">{t(`${ss}.randomness`)}</Table.Th>
+                <Table.Th ta="right">{t(`${ss}.picks`)}</Table.Th>
+                <Table.Th ta="right">{t(`${ss}.unique`)}</Table.Th>
+                <Table.Th ta="right">{t(`${ss}.repeats`)}</Table.Th>
+                <Table.Th ta="right">{t(`${ss}.bookmarked`)}</Table.Th>
+                <Table.Th ta="right">{t(`${ss}.relativeWeight`)}</Table.Th>
+                <Table.Th ta="right">{t(`${ss}.folderStreak`)}</Table.Th>
+                <Table.Th ta="right">{t(`${ss}.duration`)}</Table.Th>
+              </Table.Tr>
+            </Table.Thead>
+            <Table.Tbody>
+              {summaries.map((s) => (
+                <Table.Tr key={s.session.id}>
+                  <Table.Td style={{ whiteSpace: "nowrap" }}>
+                    {new Date(s.session.startedAt).toLocaleString()}
+                    {s.session.endedAt === null && (
+                      <Badge size="xs" ml={6} variant="light">
+                        {t(`${ss}.open`)}
+                      </Badge>
+                    )}
+                  </Table.Td>
+                  <Table.Td>{s.session.presetName ?? "—"}</Table.Td>
+                  <Table.Td>
+                    {t(`${ss}.strategies.${s.session.strategy}`)}
+                  </Table.Td>
+                  <Table.Td ta="right">
+                    {s.session.settings.randomnessLevel}
+                  </Table.Td>
+                  <Table.Td ta="right">{s.picks}</Table.Td>
+                  <Table.Td ta="right">{s.uniqueFiles}</Table.Td>
+                  <Table.Td ta="right">{s.repeats}</Table.Td>
+                  <Table.Td ta="right">{s.bookmarkedPicks}</Table.Td>
+                  <Table.Td ta="right">{fmt(s.meanRelativeWeight, 2)}</Table.Td>
+                  <Table.Td ta="right">{s.longestFolderStreak}</Table.Td>
+                  <Table.Td ta="right">
+                    {formatDuration(s.durationSeconds)}
+                  </Table.Td>
+                </Table.Tr>
+              ))}
+            </Table.Tbody>
+          </Table>
+        </ScrollArea.Autosize>
+      )}
+    </Section>
+  );
+}
@@ -1,16 +1,22 @@
-import { AppStateData, FileScore } from "../../types/filerandomiser";
+import {
+  AppStateData,
+  FileScore,
+  HistoryAggregates,
+} from "../../types/filerandomiser";
 import { fmt } from "./utils";
 
-export function buildOpensAndCumulative(history: AppStateData["history"]) {
+/** Opens over the last 30 days; `perDay` is keyed on local dates */
+export function buildOpensAndCumulative(perDay: HistoryAggregates["perDay"]) {
+  const pad = (n: number) => String(n).padStart(2, "0");
   const counts: Record<string, number> = {};
   for (let i = 0; i < 30; i++) {
     const d = new Date();
     d.setDate(d.getDate() - (29 - i));
-    counts[d.toISOString().slice(0, 10)] = 0;
+    const day = `${d.getFullYear()}-${pad(d.getMonth() + 1)}-${pad(d.getDate())}`;
+    counts[day] = 0;
   }
-  for (const h of history) {
-    const day = new Date(h.openedAt).toISOString().slice(0, 10);
-    if (counts[day] !== undefined) counts[day]++;
+  for (const { day, count } of perDay) {
+    if (counts[day] !== undefined) counts[day] = count;
   }
   let running = 0;
   return Object.entries(counts).map(([date, count]) => {
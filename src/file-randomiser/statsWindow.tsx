@@ -35,7 +35,12 @@ import {
 } from "recharts";
 import { EyeIcon, EyeSlashIcon } from "@phosphor-icons/react";
 import * as randomiserApi from "../core/api/fileRandomiserApi";
-import { AppStateData, FileScore } from "../types/filerandomiser";
+import {
+  AppStateData,
+  FileScore,
+  HistoryAggregates,
+  HistoryRow,
+} from "../types/filerandomiser";
 import {
   CH,
   fmt,
@@ -62,41 +67,52 @@ import {
   buildTopPicked,
   summariseDiagnostics,
 } from "./stats/dataBuilders";
-import {
-  buildDiagnosticsCsv,
-  buildHistoryCsv,
-  buildScoresCsv,
-} from "./stats/csvBuilders";
+import { buildScoresCsv } from "./stats/csvBuilders";
 import { StatCard } from "./stats/StatCard";
 import { Section } from "./stats/Section";
+import { SessionsSection } from "./stats/SessionsSection";
 
 const StatsWindow = () => {
   const { t } = useTranslation();
   const [appState, setAppState] = useState<AppStateData | null>(null);
   const [scores, setScores] = useState<FileScore[]>([]);
+  // Full history, oldest first, with counts worked out by the backend
+  const [history, setHistory] = useState<HistoryRow[]>([]);
+  const [aggregates, setAggregates] = useState<HistoryAggregates | null>(null);
   const [lastUpdated, setLastUpdated] = useState<Date | null>(null);
   const [sortKey, setSortKey] = useState<SortKey>("totalWeight");
   const [sortAsc, setSortAsc] = useState(false);
   const [anonymise, setAnonymise] = useState(false);
 
   const fetchData = async () => {
-    const [state, fileScores] = await Promise.all([
+    const [state, fileScores, page] = await Promise.all([
       randomiserApi.getAppState(),
       randomiserApi.getFileScores(),
+      randomiserApi.queryHistory({
+        oldestFirst: true,
+        limit: Number.MAX_SAFE_INTEGER,
+      }),
     ]);
     setAppState(state);
     setScores(fileScores);
+    setHistory(page.entries);
+    setAggregates(page.aggregates);
     setLastUpdated(new Date());
   };
 
   useEffect(() => {
     fetchData();
     let unlistenPick: (() => void) | null = null;
+    let unlistenUndo: (() => void) | null = null;
     listen("file-picked", () => fetchData()).then((fn) => {
       unlistenPick = fn;
     });
+    listen("pick-undone", () => fetchData()).then((fn) => {
+      unlistenUndo = fn;
+    });
     return () => {
       unlistenPick?.();
+      unlistenUndo?.();
     };
   }, []);
 
@@ -105,14 +121,14 @@ const StatsWindow = () => {
     // and historical picks so old history rows resolve to the same number.
     const keys = new Set<string>();
     scores.forEach((s) => keys.add(s.name));
-    appState?.history.forEach((h) => keys.add(h.name));
+    history.forEach((h) => keys.add(h.name));
     const sorted = [...keys].sort((a, b) => a.localeCompare(b));
     const m = new Map<string, string>();
     sorted.forEach((name, i) =>
       m.set(name, `File ${String(i + 1).padStart(3, "0")}`),
     );
     return m;
-  }, [scores, appState?.history]);
+  }, [scores, history]);
 
   const displayName = (_id: number, name: string) =>
     anonymise ? (anonMap.get(name) ?? `File (${name})`) : name;
@@ -124,12 +140,12 @@ const StatsWindow = () => {
 
   const lastPickedMap = useMemo(() => {
     const m = new Map<number, Date>();
-    for (const h of appState?.history ?? []) {
+    for (const h of history) {
       const d = new Date(h.openedAt);
       if (!m.has(h.id) || d > m.get(h.id)!) m.set(h.id, d);
     }
     return m;
-  }, [appState?.history]);
+  }, [history]);
 
   const derivedStats = useMemo(() => {
     const inc = scores.filter((s) => !s.isExcluded);
@@ -175,7 +191,7 @@ const StatsWindow = () => {
     });
   }, [scores, sortKey, sortAsc, pickCounts, lastPickedMap, derivedStats]);
 
-  if (!appState) {
+  if (!appState || !aggregates) {
     return (
       <Box
         p="xl"
@@ -230,27 +246,16 @@ const StatsWindow = () => {
     return H / Math.log2(uniquePicked);
   })();
 
-  const uniqueDays = new Set(
-    appState.history.map((h) =>
-      new Date(h.openedAt).toISOString().slice(0, 10),
-    ),
-  );
-  const daysActive = uniqueDays.size;
-  const firstActivity = appState.history.length
-    ? new Date(
-        Math.min(
-          ...appState.history.map((h) => new Date(h.openedAt).getTime()),
-        ),
-      )
-    : null;
+  const daysActive = aggregates.perDay.length;
+  const firstActivity = history.length ? new Date(history[0].openedAt) : null;
 
   // ── Chart data ───────────────────────────────────────────────────────────────
 
   const opensWithRolling = addRollingAvg(
-    buildOpensAndCumulative(appState.history),
+    buildOpensAndCumulative(aggregates.perDay),
   );
-  const hourOfDay = buildHourOfDay(appState.history);
-  const dayOfWeek = buildDayOfWeek(appState.history);
+  const hourOfDay = buildHourOfDay(history);
+  const dayOfWeek = buildDayOfWeek(history);
   const pickDistribution = buildPickDistribution(appState.files, pickCounts);
   const topPicked = buildTopPicked(appState.files, pickCounts, displayName);
   const pathBreakdown = buildPathBreakdown(
@@ -267,16 +272,16 @@ const StatsWindow = () => {
     (best, d) => (d.count > best.count ? d : best),
     dayOfWeek[0],
   );
-  const coverageData = buildCoverageOverTime(appState.history, included.length);
-  const repeatIntervals = buildRepeatIntervals(appState.history);
+  const coverageData = buildCoverageOverTime(history, included.length);
+  const repeatIntervals = buildRepeatIntervals(history);
   const actualVsExpected = buildActualVsExpected(
     scores,
     pickCounts,
     totalPicks,
     totalIncludedWeight,
   );
-  const diagSummary = summariseDiagnostics(appState.history);
-  const diagTrend = buildDiagnosticsTrend(appState.history);
+  const diagSummary = summariseDiagnostics(history);
+  const diagTrend = buildDiagnosticsTrend(history);
 
   // ── Table helpers ─────────────────────────────────────────────────────────────
 
@@ -317,16 +322,20 @@ const StatsWindow = () => {
       ),
     );
   const exportHistory = () =>
-    randomiserApi.saveCsv(
-      "history.csv",
-      buildHistoryCsv(appState.history, displayName),
-    );
-  const diagnosticsCount = appState.history.filter((h) => h.diagnostics).length;
+    randomiserApi.exportHistory({
+      format: "csv",
+      layout: "history",
+      anonymise,
+      labels: anonymise ? Object.fromEntries(anonMap) : undefined,
+    });
+  const diagnosticsCount = history.filter((h) => h.diagnostics).length;
   const exportDiagnostics = () =>
-    randomiserApi.saveCsv(
-      "diagnostics.csv",
-      buildDiagnosticsCsv(appState.history, displayName),
-    );
+    randomiserApi.exportHistory({
+      format: "csv",
+      layout: "diagnostics",
+      anonymise,
+      labels: anonymise ? Object.fromEntries(anonMap) : undefined,
+    });
 
   const sw = "fileRandomiser.statsWindow";
 
@@ -572,6 +581,9 @@ const StatsWindow = () => {
           </ScrollArea>
         </Section>
 
+        {/* Pick sessions */}
+        <SessionsSection />
+
         {/* ── DIAGNOSTIC CHARTS ──────────────────────────────────────────────── */}
 
         {/* Opens per day */}
@@ -1,4 +1,5 @@
 import { FileEntryBase, Bookmark } from "./common";
+import { AppSettings } from "./settings";
 
 /** Per-pick algorithm diagnostics, attached to entries created by the randomiser. */
 export type PickDiagnostics = {
@@ -22,6 +23,24 @@ export type PickDiagnostics = {
   chosenFolderStreakFactor: number;
   chosenBookmarkColor: string | null;
   chosenBookmarkGlobal: boolean;
+  blendId?: string | null;
+  blendPresetId?: string | null;
+  blendPresetProbability?: number | null;
+  /** Algorithm tuning profile active for the pick */
+  tuningProfile?: string | null;
+  /** Folder a series-mode pick continued or started */
+  seriesFolder?: string | null;
+  constraintsApplied?: number;
+  /** Constraints that had to be relaxed and that the pick then broke */
+  constraintViolations?: number;
+  /** Files chosen first but found deleted or moved, and skipped */
+  skippedMissing?: number;
+};
+
+/** Payload of the "crawl-progress" event */
+export type CrawlProgress = {
+  directories: number;
+  files: number;
 };
 
 /** History of opened files */
@@ -31,6 +50,126 @@ export type HistoryEntry = {
   path: string;
   openedAt: Date; // ISO string from Rust DateTime<Utc>
   diagnostics?: PickDiagnostics | null;
+  /** Pick session the entry belongs to; absent for files opened by hand */
+  sessionId?: string;
+};
+
+/** Filters and paging for queryHistory; `to` is exclusive */
+export type HistoryQuery = {
+  from?: string;
+  to?: string;
+  pathPrefix?: string;
+  /** Empty string matches picks with no bookmark colour */
+  color?: string;
+  text?: string;
+  offset?: number;
+  limit?: number;
+  oldestFirst?: boolean;
+};
+
+/** History entry with its position in the full history */
+export type HistoryRow = HistoryEntry & {
+  pickNumber: number;
+  color: string | null;
+};
+
+/** Counts over every entry matching a history query */
+export type HistoryAggregates = {
+  perDay: { day: string; count: number }[];
+  perFolder: { folder: string; count: number }[];
+  perColor: { color: string | null; count: number }[];
+};
+
+export type HistoryPage = {
+  entries: HistoryRow[];
+  total: number;
+  offset: number;
+  aggregates: HistoryAggregates;
+};
+
+/** Options for exportHistory; `columns` overrides the layout's columns */
+export type HistoryExportOptions = {
+  format: "csv" | "jsonLines" | "json";
+  layout: "history" | "diagnostics";
+  columns?: string[];
+  from?: string;
+  to?: string;
+  anonymise?: boolean;
+  /** Labels to export for anonymised file names, keyed by name */
+  labels?: Record<string, string>;
+};
+
+export type HistoryImportMode = "merge" | "replace";
+
+/** Result of importing a history or diagnostics export */
+export type HistoryImportSummary = {
+  rows: number;
+  imported: number;
+  duplicates: number;
+  skipped: number;
+  total: number;
+};
+
+/** Statistics from docs/randomness-paper.md over the real pick history */
+export type RandomiserMetrics = {
+  picks: number;
+  picksOutsidePool: number;
+  poolSize: number;
+  coverage: {
+    uniqueFiles: number;
+    coverage: number;
+    baselineUnique: number;
+    baselineCoverage: number;
+    actualRepeats: number;
+    expectedRepeats: number;
+    curve: { pick: number; unique: number; baseline: number }[];
+  };
+  repeatGaps: {
+    histogram: { min: number; max: number | null; count: number }[];
+    meanGap: number | null;
+    expectedMeanGap: number;
+  };
+  fit: {
+    chiSquare: number;
+    degreesOfFreedom: number;
+    zScore: number;
+    klDivergence: number;
+    unexpectedPicks: number;
+  } | null;
+  folderStreaks: StreakMetrics;
+  colorStreaks: StreakMetrics;
+};
+
+export type StreakMetrics = {
+  longest: number;
+  meanLength: number;
+  lengths: { length: number; count: number }[];
+};
+
+/** Entries rewritten by relocateLibrary, or that would be on a dry run */
+export type RelocationReport = {
+  dryRun: boolean;
+  from: string;
+  to: string;
+  pickCounts: number;
+  pickCountCollisions: number;
+  recencyEntries: number;
+  historyEntries: number;
+  globalBookmarks: number;
+  pathWeights: number;
+  presetPathWeights: number;
+  savedPaths: number;
+  files: number;
+  firstSeenEntries: number;
+  presets: PresetRelocation[];
+};
+
+export type PresetRelocation = {
+  id: string;
+  name: string;
+  paths: number;
+  bookmarks: number;
+  pathWeights: number;
 };
 
 /** Application state */
@@ -51,11 +190,90 @@ export type FileScore = {
   memoryFactor: number;
   bookmarkFactor: number;
   coverageFactor: number;
+  pickCount: number;
+  /** Pick count after half-life decay, as used by the coverage factor */
+  decayedPickCount: number;
+  newArrivalFactor: number;
   totalWeight: number;
 };
 
+/** How far through one folder series mode is */
+export type SeriesProgress = {
+  folder: string;
+  total: number;
+  seen: number;
+  /** File series mode would open next; null once all are seen */
+  next: string | null;
+  lastOpenedAt: string | null;
+};
+
+/** Every weighting factor for one candidate */
+export type PickFactors = {
+  orderScore: number;
+  memoryFactor: number;
+  bookmarkFactor: number;
+  pathFactor: number;
+  coverageFactor: number;
+  decayedPickCount: number;
+  newArrivalFactor: number;
+  colorStreakFactor: number;
+  folderStreakFactor: number;
+  hardBlocked: boolean;
+  weight: number;
+};
+
+/** Why a file is as likely as it is to come up on the next pick */
+export type PickExplanation = {
+  id: number;
+  name: string;
+  excluded: boolean;
+  candidates: number;
+  pickCount: number;
+  tuningProfile: string;
+  /** Null when the file is excluded */
+  factors: PickFactors | null;
+  probability: number;
+  rank: number | null;
+  expectedPicksUntilSeen: number | null;
+};
+
+export type PickStrategy = "weighted" | "series" | "blend";
+
+/** A run of picks under one preset, strategy and set of settings */
+export type PickSession = {
+  id: string;
+  startedAt: string;
+  /** Null while the session is still open */
+  endedAt: string | null;
+  lastPickAt: string | null;
+  /** Started by the user rather than by an idle gap or a change of setup */
+  explicit: boolean;
+  presetId: string | null;
+  presetName: string | null;
+  strategy: PickStrategy;
+  blendId: string | null;
+  /** Randomiser settings at the start of the session */
+  settings: AppSettings["fileRandomiser"];
+};
+
+export type SessionSummary = {
+  session: PickSession;
+  picks: number;
+  uniqueFiles: number;
+  repeats: number;
+  bookmarkedPicks: number;
+  meanCandidates: number;
+  /** Mean chosen weight over mean candidate weight; uniform picking gives 1 */
+  meanRelativeWeight: number;
+  longestFolderStreak: number;
+  constraintViolations: number;
+  durationSeconds: number;
+};
+
 /** Preset for randomiser configuration */
 export type RandomiserPreset = {
+  /** Set by the backend when the preset is written */
+  schemaVersion?: number;
   id: string;
   name: string;
   paths: SavedPath[];
@@ -65,6 +283,71 @@ export type RandomiserPreset = {
   pathWeights?: Record<string, number>;
 };
 
+/** Preset or blend file that failed to load */
+export type InvalidPresetFile = {
+  fileName: string;
+  error: string;
+};
+
+/** Presets on disk plus any files that failed to load */
+export type PresetListing = {
+  presets: RandomiserPreset[];
+  invalid: InvalidPresetFile[];
+};
+
+/** One preset inside a blend; weights are relative shares */
+export type BlendComponent = {
+  presetId: string;
+  weight: number;
+};
+
+/** Weighted mix of presets picked from as one pool */
+export type PresetBlend = {
+  id: string;
+  name: string;
+  components: BlendComponent[];
+};
+
+/** Blends on disk plus any files that failed to load */
+export type BlendListing = {
+  blends: PresetBlend[];
+  invalid: InvalidPresetFile[];
+};
+
+/** Result of a blended pick */
+export type BlendPick = {
+  file: FileEntry;
+  presetId: string;
+  presetName: string;
+  presetProbability: number;
+};
+
+/** Portable single-file export of a preset */
+export type PresetBundle = {
+  bundleVersion: number;
+  exportedAt: string;
+  roots: string[];
+  preset: RandomiserPreset;
+};
+
+/** Rewrites paths under `from` to the same place under `to` */
+export type PathRemap = {
+  from: string;
+  to: string;
+};
+
+export type BundleRoot = {
+  path: string;
+  exists: boolean;
+};
+
+/** Bundle contents shown before import, for choosing remaps */
+export type PresetBundlePreview = {
+  bundle: PresetBundle;
+  roots: BundleRoot[];
+  idConflict: boolean;
+};
+
 /** State of current preset in UI */
 export type PresetState = {
   currentId: string | null;
@@ -93,6 +376,9 @@ export type FileEntry = FileEntryBase & {
   excluded: boolean;
   hash: string;
   bookmark?: BookmarkInfo;
+  /** When a crawl first found this path */
+  firstSeen?: string;
+  modified?: string;
 };
 
 /** Extra info for bookmarks */
@@ -12,6 +12,64 @@ export type ColorWeightEntry = {
   global: number;
 };
 
+/** A hard rule on the picks within one session */
+export type PickConstraint =
+  | { kind: "maxPerFolder"; max: number }
+  | { kind: "colorEvery"; color: string; every: number }
+  | { kind: "noRepeatExtension" };
+
+/** What opening a picked or audited file does */
+export type OpenAction =
+  | { kind: "default" }
+  | { kind: "application"; path: string }
+  /** `{path}` and `{dir}` are replaced with the quoted file path and folder */
+  | { kind: "command"; template: string }
+  | { kind: "reveal" }
+  | { kind: "copyPath" }
+  | { kind: "openFolder" };
+
+/** The first rule matching a file's extension and the active preset wins */
+export type OpenActionRule = {
+  /** Lowercase without the dot; empty matches every file */
+  extensions: string[];
+  presetId?: string | null;
+  action: OpenAction;
+};
+
+/** Weighted sampling, or next-unseen-file-per-folder series mode */
+export type PickMode = "weighted" | "series";
+
+export type SeriesSettings = {
+  /** Chance of continuing the most recently watched unfinished folder */
+  continueProbability: number;
+};
+
+/** Constants behind the randomiser's weighting; see `AlgorithmTuning` */
+export type AlgorithmTuning = {
+  /** Named profile, or "custom" once any value is changed */
+  profile: string;
+  recencyWindowScale: number;
+  recencyWindowMin: number;
+  recencyWindowMax: number;
+  orderSigma: number;
+  orderBoost: number;
+  colorStreakLast: number;
+  colorStreakRecent: number;
+  folderStreakLast: number;
+  folderStreakRecent: number;
+  hardBlockMax: number;
+};
+
+export type NewArrivalsBoost = {
+  enabled: boolean;
+  /** Weight multiplier for a file that has only just arrived */
+  boost: number;
+  /** Days for the boost to fall halfway back to 1 */
+  halfLifeDays: number;
+  /** Also count a recently modified file as a new arrival */
+  useModified: boolean;
+};
+
 export type BookmarkPreference = {
   enabled: boolean;
   colors: Record<string, ColorWeightEntry>;
@@ -34,19 +92,34 @@ export type FileAuditorSettings = {
 };
 
 export type AppSettings = {
+  schemaVersion?: number;
   language: LanguageOption;
   darkMode: DarkModeOption;
   customBackground?: string;
   fileRandomiser: {
     allowProcessTracking: boolean;
     randomnessLevel: number;
-    globalBookmarks: Bookmark[];
+    pickMode?: PickMode;
+    series?: SeriesSettings;
+    /** Legacy: global bookmarks now live in bookmarks.json */
+    globalBookmarks?: Bookmark[];
     bookmarkPreference: BookmarkPreference;
     pathWeights?: Record<string, number>;
     pathWeightsEnabled?: boolean;
     historyRetentionDays: number;
+    /** Days for a pick to count half as much towards coverage; 0 = no decay */
+    pickCountHalfLifeDays?: number;
+    newArrivals?: NewArrivalsBoost;
+    tuning?: AlgorithmTuning;
+    /** Hard per-session rules for weighted picks */
+    pickConstraints?: PickConstraint[];
+    /** Minutes without a pick before a new session starts; 0 = never */
+    sessionIdleMinutes?: number;
     persistRecency?: boolean;
+    /** User-defined `${NAME}` roots for portable preset paths */
+    pathVariables?: Record<string, string>;
   };
   fileAuditor?: FileAuditorSettings;
   bookmarkColors: BookmarkColorOption[];
+  openActions?: OpenActionRule[];
 };